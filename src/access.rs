@@ -0,0 +1,119 @@
+use std::io::Write;
+use crate::ai::SIGHT_RANGE;
+use crate::item::ITEMS;
+use crate::room::RoomKind;
+use crate::{Dir, Game, GameVars};
+
+// "2 east, 1 north" from the hero to a tile
+fn relative(dx: isize, dy: isize) -> String {
+    let mut parts = Vec::new();
+
+    match dx {
+        0 => {},
+        d if d > 0 => parts.push(format!("{} east", d)),
+        d => parts.push(format!("{} west", -d)),
+    }
+
+    match dy {
+        0 => {},
+        d if d > 0 => parts.push(format!("{} south", d)),
+        d => parts.push(format!("{} north", -d)),
+    }
+
+    if parts.is_empty() { "here".to_string() } else { parts.join(", ") }
+}
+
+impl GameVars {
+    // what a sighted player would take in at a glance, in a few short lines
+    pub fn surroundings(&self) -> Vec<String> {
+        if let Some(shop) = self.shop.as_ref() {
+            let side = if shop.selling { "Selling" } else { "Buying" };
+
+            let entry = match self.shop_entries(shop.selling).get(shop.selected) {
+                Some(&kind) => {
+                    let price = if shop.selling { ITEMS[kind].sell_price() } else { ITEMS[kind].price };
+                    format!("{} for {} gold", ITEMS[kind].name, price)
+                },
+                None => "nothing".to_string(),
+            };
+
+            return vec![format!("{}: {}. You have {} gold.", side, entry, self.gold)];
+        }
+
+        let (row, col) = self.hero_cell();
+        let doors = self.visible_doors(row, col);
+
+        let exits: Vec<&str> = [(Dir::Up, "north"), (Dir::Right, "east"), (Dir::Down, "south"), (Dir::Left, "west")]
+            .iter()
+            .filter(|(dir, _)| doors & (1 << *dir as usize) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+
+        let room = match self.rooms.get(row, col) {
+            RoomKind::Treasure => " A treasure chest.",
+            RoomKind::Shrine => " A shrine.",
+            RoomKind::Merchant => " A merchant.",
+            RoomKind::Lair => " A monster lair.",
+            RoomKind::Trap | RoomKind::Empty => "",
+        };
+
+        let stairs = match (self.stairs.down == (row, col), self.stairs.up == Some((row, col))) {
+            (true, _) => " Stairs down.",
+            (_, true) => " Stairs up.",
+            _ => "",
+        };
+
+        let exits = if exits.is_empty() { "none".to_string() } else { exits.join(", ") };
+        let mut lines = vec![format!("Exits: {}.{}{}", exits, room, stairs)];
+
+        let near = |x: usize, y: usize| {
+            let dist = x.abs_diff(self.hero_pos_x) + y.abs_diff(self.hero_pos_y);
+            dist <= SIGHT_RANGE && self.visit_map.known(y / self.base, x / self.base)
+        };
+        let offset = |x: usize, y: usize| relative(x as isize - self.hero_pos_x as isize, y as isize - self.hero_pos_y as isize);
+
+        let seen: Vec<String> = self.monsters.iter()
+            .filter(|m| near(m.pos_x, m.pos_y))
+            .map(|m| format!("{} {}", m.kind().name, offset(m.pos_x, m.pos_y)))
+            .chain(self.traps.iter()
+                .filter(|t| t.found && near(t.pos_x, t.pos_y))
+                .map(|t| format!("{} {}", t.kind.name(), offset(t.pos_x, t.pos_y))))
+            .collect();
+
+        if !seen.is_empty() {
+            lines.push(format!("You see: {}.", seen.join("; ")));
+        }
+
+        lines
+    }
+}
+
+impl Game {
+    // logs the surroundings after a turn, unless nothing changed
+    pub fn announce(&mut self) {
+        if !self.describe {
+            return;
+        }
+
+        let lines = self.vars.surroundings();
+
+        if lines != self.described {
+            for line in &lines {
+                self.vars.log(line.clone());
+            }
+            self.described = lines;
+        }
+    }
+
+    // linear mode prints the log as it grows instead of drawing windows
+    pub fn print_log(&mut self) {
+        let mut out = std::io::stdout();
+
+        for msg in &self.vars.log[self.printed..] {
+            let _ = write!(out, "{}\r\n", msg);
+        }
+
+        self.printed = self.vars.log.len();
+        let _ = out.flush();
+    }
+}
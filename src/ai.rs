@@ -0,0 +1,249 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use rand::Rng;
+use crate::monster::Behavior;
+use crate::{Dir, GameVars};
+
+pub static SIGHT_RANGE: usize = 8;
+static WAKE_CHANCE: usize = 3;
+static UNREACHABLE: usize = usize::MAX;
+
+const STEPS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+// distance of every floor tile to a single goal (usually the hero), computed
+// once per turn and shared by all monsters
+pub struct DijkstraMap {
+    cols: usize,
+    dist: Vec<usize>,
+}
+
+impl DijkstraMap {
+    pub fn new(game: &GameVars, goal_x: usize, goal_y: usize) -> DijkstraMap {
+        let cols = game.map.matrix.cols * game.base;
+        let rows = game.map.matrix.rows * game.base;
+        let mut map = DijkstraMap { cols, dist: vec![UNREACHABLE; cols * rows] };
+        let mut queue = VecDeque::new();
+
+        map.dist[goal_y * cols + goal_x] = 0;
+        queue.push_back((goal_x, goal_y));
+
+        // all steps cost the same, so plain BFS gives Dijkstra distances
+        while let Some((x, y)) = queue.pop_front() {
+            let d = map.get(x, y);
+
+            for (nx, ny) in game.tile_neighbors(x, y) {
+                if map.get(nx, ny) == UNREACHABLE {
+                    map.dist[ny * cols + nx] = d + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        map
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> usize {
+        self.dist.get(y * self.cols + x).copied().unwrap_or(UNREACHABLE)
+    }
+}
+
+fn manhattan(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+// generic A*, returns the path without the start node
+fn astar<N, F>(start: N, goal: N, neighbors: F, heuristic: fn(N, N) -> usize) -> Option<Vec<N>>
+where
+    N: Copy + Eq + std::hash::Hash + Ord,
+    F: Fn(N) -> Vec<N>,
+{
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<N, N> = HashMap::new();
+    let mut cost: HashMap<N, usize> = HashMap::new();
+
+    cost.insert(start, 0);
+    open.push(Reverse((heuristic(start, goal), start)));
+
+    while let Some(Reverse((_, node))) = open.pop() {
+        if node == goal {
+            let mut path = vec![node];
+            let mut cur = node;
+
+            while let Some(&prev) = came_from.get(&cur) {
+                if prev == start {
+                    break;
+                }
+                path.push(prev);
+                cur = prev;
+            }
+
+            path.reverse();
+            return Some(path);
+        }
+
+        let g = cost[&node];
+
+        for next in neighbors(node) {
+            if cost.get(&next).is_none_or(|&c| g + 1 < c) {
+                cost.insert(next, g + 1);
+                came_from.insert(next, node);
+                open.push(Reverse((g + 1 + heuristic(next, goal), next)));
+            }
+        }
+    }
+
+    None
+}
+
+impl GameVars {
+    // path of (row, col) cells through open doors of the coarse room graph
+    pub fn room_path(&self, from: (usize, usize), to: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+
+        let neighbors = |(row, col): (usize, usize)| {
+            [Dir::Up, Dir::Right, Dir::Down, Dir::Left]
+                .into_iter()
+                .filter(|&dir| self.visible_doors(row, col) & (1 << dir as usize) != 0)
+                .filter_map(|dir| self.map.get_neighbor(row, col, dir))
+                .collect()
+        };
+
+        astar(from, to, neighbors, manhattan)
+    }
+
+    pub fn tile_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        STEPS.iter()
+            .map(|&(dx, dy)| ((x as isize + dx) as usize, (y as isize + dy) as usize))
+            .filter(|&(nx, ny)| self.is_walkable(nx, ny))
+            .collect()
+    }
+
+    // path of (x, y) tiles on the fine grid
+    pub fn tile_path(&self, from: (usize, usize), to: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+
+        astar(from, to, |(x, y)| self.tile_neighbors(x, y), manhattan)
+    }
+
+    // next tile towards a far away goal: rooms first, then tiles inside them
+    pub fn path_step(&self, from: (usize, usize), to: (usize, usize)) -> Option<(usize, usize)> {
+        let from_cell = (from.1 / self.base, from.0 / self.base);
+        let to_cell = (to.1 / self.base, to.0 / self.base);
+
+        let goal = match self.room_path(from_cell, to_cell)?.first() {
+            Some(&(row, col)) if (row, col) != to_cell => (col * self.base + self.base / 2, row * self.base + self.base / 2),
+            _ => to,
+        };
+
+        self.tile_path(from, goal)?.first().copied()
+    }
+
+    fn free_for_monster(&self, x: usize, y: usize) -> bool {
+        self.is_walkable(x, y) && self.monster_at(x, y).is_none() && (x, y) != (self.hero_pos_x, self.hero_pos_y)
+    }
+
+    fn move_monster(&mut self, idx: usize, to: Option<(usize, usize)>) -> bool {
+        match to {
+            Some((x, y)) if self.free_for_monster(x, y) => {
+                self.monsters[idx].pos_x = x;
+                self.monsters[idx].pos_y = y;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn pick_behavior(&self, idx: usize, dmap: &DijkstraMap) -> Behavior {
+        let m = &self.monsters[idx];
+        let sees_hero = dmap.get(m.pos_x, m.pos_y) <= SIGHT_RANGE;
+
+        if m.hp * 4 <= m.max_hp {
+            return Behavior::Flee;
+        }
+
+        match m.home {
+            // guards only leave their post for a hero inside the guarded room
+            Some((hx, hy)) => {
+                let home_cell = (hy / self.base, hx / self.base);
+
+                if sees_hero && self.hero_cell() == home_cell { Behavior::Chase } else { Behavior::Guard }
+            },
+            None if sees_hero => Behavior::Chase,
+            None => Behavior::Wander,
+        }
+    }
+
+    // moves a monster which is not attacking this turn
+    pub fn monster_act(&mut self, idx: usize, dmap: &DijkstraMap) {
+        if !self.monsters[idx].awake {
+            let (row, col) = self.hero_cell();
+            let m = &self.monsters[idx];
+            let name = m.kind().name;
+
+            if (m.pos_y / self.base, m.pos_x / self.base) == (row, col) && self.rng.random_range(0..WAKE_CHANCE) == 0 {
+                self.monsters[idx].awake = true;
+                self.log(format!("The {} notices you.", name));
+            }
+            return;
+        }
+
+        let behavior = self.pick_behavior(idx, dmap);
+        let (x, y) = (self.monsters[idx].pos_x, self.monsters[idx].pos_y);
+        self.monsters[idx].behavior = behavior;
+
+        match behavior {
+            Behavior::Chase => {
+                let step = self.tile_neighbors(x, y).into_iter()
+                    .filter(|&(nx, ny)| self.free_for_monster(nx, ny))
+                    .min_by_key(|&(nx, ny)| dmap.get(nx, ny));
+                self.move_monster(idx, step);
+            },
+            Behavior::Flee => {
+                let here = dmap.get(x, y);
+                let step = self.tile_neighbors(x, y).into_iter()
+                    .filter(|&(nx, ny)| self.free_for_monster(nx, ny) && dmap.get(nx, ny) > here)
+                    .max_by_key(|&(nx, ny)| dmap.get(nx, ny));
+                self.move_monster(idx, step);
+            },
+            Behavior::Guard => {
+                let home = self.monsters[idx].home.unwrap();
+                if (x, y) != home {
+                    self.move_monster(idx, self.path_step((x, y), home));
+                }
+            },
+            Behavior::Wander => self.wander(idx),
+        }
+    }
+
+    // walks towards a random neighbouring room, picks a new one on arrival
+    fn wander(&mut self, idx: usize) {
+        let (x, y) = (self.monsters[idx].pos_x, self.monsters[idx].pos_y);
+
+        if self.monsters[idx].target.is_none_or(|t| t == (x, y)) {
+            let (row, col) = (y / self.base, x / self.base);
+            let exits: Vec<(usize, usize)> = [Dir::Up, Dir::Right, Dir::Down, Dir::Left]
+                .into_iter()
+                .filter(|&dir| self.visible_doors(row, col) & (1 << dir as usize) != 0)
+                .filter_map(|dir| self.map.get_neighbor(row, col, dir))
+                .collect();
+
+            if exits.is_empty() {
+                return;
+            }
+
+            let (t_row, t_col) = exits[self.rng.random_range(0..exits.len())];
+            self.monsters[idx].target = Some((t_col * self.base + self.base / 2, t_row * self.base + self.base / 2));
+        }
+
+        let target = self.monsters[idx].target.unwrap();
+
+        if !self.move_monster(idx, self.path_step((x, y), target)) {
+            // blocked, try somewhere else next turn
+            self.monsters[idx].target = None;
+        }
+    }
+}
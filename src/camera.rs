@@ -0,0 +1,105 @@
+use crate::GameVars;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CameraMode {
+    // focus always in the middle of the window
+    Center,
+    // scrolls only when the focus gets close to a window edge
+    Deadzone,
+    // jumps from room to room, keeping the whole room in view
+    Room,
+}
+
+pub static CAMERA_MODES: [(CameraMode, &str); 3] = [
+    (CameraMode::Center, "center"),
+    (CameraMode::Deadzone, "deadzone"),
+    (CameraMode::Room, "room"),
+];
+
+impl CameraMode {
+    pub fn by_name(name: &str) -> Option<CameraMode> {
+        CAMERA_MODES.iter().find(|(_, n)| *n == name).map(|(m, _)| *m)
+    }
+
+    pub fn name(self) -> &'static str {
+        CAMERA_MODES.iter().find(|(m, _)| *m == self).map(|(_, n)| *n).unwrap()
+    }
+}
+
+// map tile shown in the top-left corner of the map window; negative
+// when the map is smaller than the window and gets centered in it
+pub struct Camera {
+    pub mode: CameraMode,
+    pad: Option<(isize, isize)>,
+}
+
+impl Camera {
+    pub fn new(mode: CameraMode) -> Camera {
+        Camera { mode, pad: None }
+    }
+
+    pub fn pad(&self) -> (isize, isize) {
+        self.pad.unwrap_or((0, 0))
+    }
+
+    pub fn cycle(&mut self) {
+        let idx = CAMERA_MODES.iter().position(|(m, _)| *m == self.mode).unwrap();
+        self.mode = CAMERA_MODES[(idx + 1) % CAMERA_MODES.len()].0;
+    }
+}
+
+// keeps the window inside the map, or the map in the middle of the window
+fn clamp(pad: isize, win: usize, size: usize) -> isize {
+    if size <= win {
+        -(((win - size) / 2) as isize)
+    } else {
+        pad.clamp(0, (size - win) as isize)
+    }
+}
+
+// moves the window just enough for the focus to stay out of the margins
+fn follow(pad: isize, focus: isize, win: usize) -> isize {
+    let margin = (win / 4) as isize;
+    let far = win as isize - 1 - margin;
+
+    if focus - pad < margin {
+        focus - margin
+    } else if focus - pad > far {
+        focus - far
+    } else {
+        pad
+    }
+}
+
+impl GameVars {
+    // where the map window is looking at
+    pub fn camera_focus(&self) -> (usize, usize) {
+        match (self.travel_cursor, self.look_cursor) {
+            (Some((row, col)), _) => (col * self.base + self.base / 2, row * self.base + self.base / 2),
+            (None, Some(tile)) => tile,
+            (None, None) => (self.hero_pos_x, self.hero_pos_y),
+        }
+    }
+
+    // called before drawing, the deadzone needs to know where it was
+    pub fn update_camera(&mut self, win_w: usize, win_h: usize) {
+        let (focus_x, focus_y) = self.camera_focus();
+        let centered = |fx: usize, fy: usize| (fx as isize - (win_w / 2) as isize, fy as isize - (win_h / 2) as isize);
+
+        let (pad_x, pad_y) = match (self.camera.mode, self.camera.pad) {
+            (CameraMode::Deadzone, Some((x, y))) => {
+                (follow(x, focus_x as isize, win_w), follow(y, focus_y as isize, win_h))
+            },
+            (CameraMode::Room, _) => {
+                let half = self.base / 2;
+                centered(focus_x / self.base * self.base + half, focus_y / self.base * self.base + half)
+            },
+            _ => centered(focus_x, focus_y),
+        };
+
+        let map_w = self.map.matrix.cols * self.base;
+        let map_h = self.map.matrix.rows * self.base;
+
+        self.camera.pad = Some((clamp(pad_x, win_w, map_w), clamp(pad_y, win_h, map_h)));
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::PathBuf;
+use crate::camera::{CameraMode, CAMERA_MODES};
+use crate::config::{MAX_BLOCK_CHANCE, MAX_MAP_SIZE, MAX_START_LEVEL, MIN_MAP_SIZE};
+use crate::palette::{Palette, PALETTES};
+
+pub static USAGE: &str = "\
+Usage: dsix [OPTIONS]
+
+Options:
+  --seed <N>             seed of the run (random by default)
+  --start-level <N>      level to start on, 1 to 99
+  --map-size <W>x<H>     level size in rooms, each between 3 and 50
+  --block-chance <N>     chance (0-10) of a room losing doors
+  --no-color             plain output without colors
+  --palette <NAME>       colors: default, high-contrast or colorblind
+  --camera <MODE>        map view: center, deadzone or room
+  --describe             log the surroundings after every turn
+  --linear               print the log line by line instead of drawing
+                         the screen, for screen readers (implies --describe)
+  --replay <FILE>        watch a recorded run
+  --load <FILE>          continue a recorded run
+  --config <FILE>        read settings from a config file
+  --difficulty <NAME>    easy, hard or a preset from the config file
+  -h, --help             print this help
+  -V, --version          print version";
+
+pub struct Options {
+    pub seed: Option<u64>,
+    pub start_level: Option<usize>,
+    pub map_size: Option<(usize, usize)>,
+    pub block_chance: Option<usize>,
+    pub color: bool,
+    pub palette: &'static Palette,
+    pub camera: CameraMode,
+    pub describe: bool,
+    pub linear: bool,
+    pub replay: Option<PathBuf>,
+    pub load: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub difficulty: Option<String>,
+}
+
+pub enum Command {
+    Run(Options),
+    Help,
+    Version,
+}
+
+fn parse_number<T: std::str::FromStr>(opt: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("{} expects a number, got '{}'", opt, value))
+}
+
+fn parse_map_size(value: &str) -> Result<(usize, usize), String> {
+    let (w, h) = value.split_once(['x', 'X'])
+        .ok_or(format!("--map-size expects <W>x<H>, got '{}'", value))?;
+    let w = parse_number::<usize>("--map-size", w)?;
+    let h = parse_number::<usize>("--map-size", h)?;
+
+    for v in [w, h] {
+        if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&v) {
+            return Err(format!("--map-size sides must be between {} and {}, got {}", MIN_MAP_SIZE, MAX_MAP_SIZE, v));
+        }
+    }
+
+    Ok((w, h))
+}
+
+// accepts both "--opt value" and "--opt=value"
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let mut opts = Options {
+        seed: None,
+        start_level: None,
+        map_size: None,
+        block_chance: None,
+        color: std::env::var_os("NO_COLOR").is_none(),
+        palette: &PALETTES[0],
+        camera: CameraMode::Center,
+        describe: false,
+        linear: false,
+        replay: None,
+        load: None,
+        config: None,
+        difficulty: None,
+    };
+
+    let mut it = args.iter();
+
+    while let Some(arg) = it.next() {
+        let (opt, inline) = match arg.split_once('=') {
+            Some((o, v)) if o.starts_with("--") => (o, Some(v.to_string())),
+            _ => (arg.as_str(), None),
+        };
+
+        let mut value = || -> Result<String, String> {
+            inline.clone().or_else(|| it.next().cloned()).ok_or(format!("{} needs a value", opt))
+        };
+
+        match opt {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--seed" => opts.seed = Some(parse_number("--seed", &value()?)?),
+            "--start-level" => {
+                let level = parse_number::<usize>("--start-level", &value()?)?;
+                if !(1..=MAX_START_LEVEL).contains(&level) {
+                    return Err(format!("--start-level must be between 1 and {}, got {}", MAX_START_LEVEL, level));
+                }
+                opts.start_level = Some(level);
+            },
+            "--map-size" => opts.map_size = Some(parse_map_size(&value()?)?),
+            "--block-chance" => {
+                let chance = parse_number::<usize>("--block-chance", &value()?)?;
+                if chance > MAX_BLOCK_CHANCE {
+                    return Err(format!("--block-chance must be between 0 and {}, got {}", MAX_BLOCK_CHANCE, chance));
+                }
+                opts.block_chance = Some(chance);
+            },
+            "--no-color" => opts.color = false,
+            "--palette" => {
+                let name = value()?;
+                let names: Vec<&str> = PALETTES.iter().map(|p| p.name).collect();
+                opts.palette = Palette::by_name(&name)
+                    .ok_or(format!("--palette must be one of {}, got '{}'", names.join(", "), name))?;
+            },
+            "--camera" => {
+                let name = value()?;
+                let names: Vec<&str> = CAMERA_MODES.iter().map(|(_, n)| *n).collect();
+                opts.camera = CameraMode::by_name(&name)
+                    .ok_or(format!("--camera must be one of {}, got '{}'", names.join(", "), name))?;
+            },
+            "--describe" => opts.describe = true,
+            "--linear" => {
+                opts.linear = true;
+                opts.describe = true;
+            },
+            "--replay" => opts.replay = Some(PathBuf::from(value()?)),
+            "--load" => opts.load = Some(PathBuf::from(value()?)),
+            "--config" => opts.config = Some(PathBuf::from(value()?)),
+            "--difficulty" => opts.difficulty = Some(value()?),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    // a recorded run brings its own seed and level setup
+    if let Some(file) = opts.replay.as_ref().or(opts.load.as_ref()) {
+        if opts.replay.is_some() && opts.load.is_some() {
+            return Err("--replay and --load can't be used together".to_string());
+        }
+
+        let fixed = opts.seed.is_some() || opts.start_level.is_some() || opts.map_size.is_some() || opts.block_chance.is_some()
+            || opts.config.is_some() || opts.difficulty.is_some();
+        if fixed {
+            return Err(format!("{} already sets seed, level, map size and game settings", file.display()));
+        }
+
+        if !file.is_file() {
+            return Err(format!("no such file: {}", file.display()));
+        }
+    }
+
+    match opts.config.as_ref() {
+        Some(file) if !file.is_file() => return Err(format!("no such file: {}", file.display())),
+        _ => {},
+    }
+
+    Ok(Command::Run(opts))
+}
@@ -0,0 +1,177 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use toml::Table;
+
+pub static CONFIG_FILE: &str = "config.toml";
+pub static MIN_MAP_SIZE: usize = 3;
+pub static MAX_MAP_SIZE: usize = 50;
+pub static MAX_BLOCK_CHANCE: usize = 10;
+pub static MAX_START_LEVEL: usize = 99;
+static MIN_BASE: usize = 5;
+
+// balance of a run; every field can be left out of the config file
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub block_chance: usize,
+    pub attack: usize,
+    pub armor: usize,
+    pub speed: usize,
+    pub exp: usize,
+    pub hp: usize,
+    pub gold: usize,
+    pub monsters: usize,
+    pub attack_dice: usize,
+    pub trap_difficulty: usize,
+    pub lair_size: usize,
+    pub level_width: usize,
+    pub level_height: usize,
+    // size of a room in tiles
+    pub base: usize,
+    pub door_width: usize,
+    // every this many levels a boss floor, 0 for none
+    pub boss_every: usize,
+    // turns a torch burns, 0 to play without one
+    pub torch: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            block_chance: 5,
+            attack: 2,
+            armor: 0,
+            speed: 2,
+            exp: 0,
+            hp: 20,
+            gold: 0,
+            monsters: 2,
+            attack_dice: 2,
+            trap_difficulty: 5,
+            lair_size: 3,
+            level_width: 8,
+            level_height: 8,
+            base: 9,
+            door_width: 3,
+            boss_every: 5,
+            torch: 0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.block_chance > MAX_BLOCK_CHANCE {
+            return Err(format!("block_chance must be between 0 and {}, got {}", MAX_BLOCK_CHANCE, self.block_chance));
+        }
+
+        for (name, v) in [("level_width", self.level_width), ("level_height", self.level_height)] {
+            if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&v) {
+                return Err(format!("{} must be between {} and {}, got {}", name, MIN_MAP_SIZE, MAX_MAP_SIZE, v));
+            }
+        }
+
+        for (name, v) in [("hp", self.hp), ("attack_dice", self.attack_dice), ("lair_size", self.lair_size)] {
+            if v == 0 {
+                return Err(format!("{} must be 1 or more", name));
+            }
+        }
+
+        // doors sit in the middle of a wall, so a room needs a center tile
+        // and walls around a door at least one tile wide
+        if self.base < MIN_BASE || self.base.is_multiple_of(2) {
+            return Err(format!("base must be odd and at least {}, got {}", MIN_BASE, self.base));
+        }
+
+        if self.door_width.is_multiple_of(2) || self.door_width > self.base - 2 {
+            return Err(format!("door_width must be odd and at most {} for base {}, got {}", self.base - 2, self.base, self.door_width));
+        }
+
+        Ok(())
+    }
+
+    pub fn from_table(table: Table) -> Result<Settings, String> {
+        let settings: Settings = table.try_into().map_err(|e: toml::de::Error| e.message().to_string())?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    pub fn to_table(self) -> Table {
+        Table::try_from(self).expect("settings are plain numbers")
+    }
+}
+
+// $XDG_CONFIG_HOME/dsix/config.toml, ~/.config/dsix/config.toml or %APPDATA%\dsix\config.toml
+pub fn config_path() -> PathBuf {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    base.join("dsix").join(CONFIG_FILE)
+}
+
+// always there, a preset of the same name in the config file replaces them
+static BUILTIN_PRESETS: &str = "
+[easy]
+hp = 30
+gold = 20
+monsters = 1
+trap_difficulty = 3
+
+[hard]
+hp = 15
+monsters = 3
+trap_difficulty = 7
+lair_size = 4
+torch = 300
+";
+
+// base settings and presets of the config file, if there is one
+fn read(path: Option<&Path>) -> Result<(Table, Table), String> {
+    let mut presets: Table = BUILTIN_PRESETS.parse().unwrap();
+    let Some(path) = path else { return Ok((Table::new(), presets)) };
+
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut table: Table = text.parse().map_err(|e: toml::de::Error| format!("{}: {}", path.display(), e.message()))?;
+
+    match table.remove("presets") {
+        Some(toml::Value::Table(own)) => presets.extend(own),
+        Some(_) => return Err(format!("{}: presets must be a table", path.display())),
+        None => {},
+    }
+
+    Ok((table, presets))
+}
+
+// names of the difficulty presets, for the title screen
+pub fn preset_names(path: Option<&Path>) -> Result<Vec<String>, String> {
+    read(path).map(|(_, presets)| presets.keys().cloned().collect())
+}
+
+// top-level keys are the base settings; a difficulty picks one of the
+// [presets.<name>] tables, whose keys override the base ones
+pub fn load(path: Option<&Path>, difficulty: Option<&str>) -> Result<Settings, String> {
+    let (mut table, presets) = read(path)?;
+    let source = path.map(|p| format!("{}: ", p.display())).unwrap_or_default();
+
+    let chosen = match table.remove("difficulty") {
+        Some(toml::Value::String(name)) => Some(name),
+        Some(_) => return Err(format!("{}difficulty must be a preset name", source)),
+        None => None,
+    };
+
+    // the command line wins over the file
+    if let Some(name) = difficulty.map(str::to_string).or(chosen) {
+        match presets.get(&name) {
+            Some(toml::Value::Table(preset)) => table.extend(preset.clone()),
+            Some(_) => return Err(format!("{}preset '{}' must be a table", source, name)),
+            None => return Err(format!("{}no difficulty preset named '{}'", source, name)),
+        }
+    }
+
+    Settings::from_table(table).map_err(|e| format!("{}{}", source, e))
+}
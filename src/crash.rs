@@ -0,0 +1,84 @@
+use std::backtrace::Backtrace;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::score::data_dir;
+use crate::terminal;
+use crate::Game;
+
+// what a crash report needs to know about the run, refreshed every turn
+struct CrashContext {
+    seed: u64,
+    level: usize,
+    hero: (usize, usize),
+    turn: usize,
+    replay: Option<PathBuf>,
+}
+
+static CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+
+pub fn update_context(game: &Game) {
+    let context = CrashContext {
+        seed: game.setup.seed,
+        level: game.vars.level,
+        hero: (game.vars.hero_pos_x, game.vars.hero_pos_y),
+        turn: game.vars.turn,
+        replay: game.recorder.as_ref().map(|r| r.path.clone()),
+    };
+
+    if let Ok(mut ctx) = CONTEXT.lock() {
+        *ctx = Some(context);
+    }
+}
+
+// restores the terminal first, so the panic message ends up readable
+pub fn install_hook() {
+    panic::set_hook(Box::new(|info| {
+        terminal::restore();
+
+        match write_report(info) {
+            Ok(path) => eprintln!("dsix crashed, report written to {}", path.display()),
+            Err(e) => eprintln!("dsix crashed and could not write a report: {}", e),
+        }
+
+        eprintln!("{}", info);
+    }));
+}
+
+fn write_report(info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    fs::create_dir_all(data_dir())?;
+
+    let path = data_dir().join(format!("crash-{}.txt", time));
+    let mut file = File::create(&path)?;
+
+    writeln!(file, "dsix {} crash report", env!("CARGO_PKG_VERSION"))?;
+    writeln!(file)?;
+    writeln!(file, "{}", info)?;
+    writeln!(file)?;
+
+    // a poisoned lock still holds the last good context
+    let ctx = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+
+    match ctx.as_ref() {
+        Some(c) => {
+            writeln!(file, "Seed:  {}", c.seed)?;
+            writeln!(file, "Level: {}", c.level)?;
+            writeln!(file, "Hero:  {},{}", c.hero.0, c.hero.1)?;
+            writeln!(file, "Turn:  {}", c.turn)?;
+
+            if let Some(replay) = c.replay.as_ref() {
+                writeln!(file, "Replay: {}", replay.display())?;
+            }
+        },
+        None => writeln!(file, "Crashed before the run started.")?,
+    }
+
+    writeln!(file)?;
+    writeln!(file, "{}", Backtrace::force_capture())?;
+
+    Ok(path)
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+use rand::Rng;
+
+// Dice expressions in the `NdS+M` notation used by the paper rules.
+// Sides default to 6, so "2d" and "2d+1" are a pair of d6.
+pub static DEFAULT_SIDES: usize = 6;
+
+// more dice than this is a typo, and rolling them would take forever
+pub static MAX_COUNT: usize = 100;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dice {
+    pub count: usize,
+    pub sides: usize,
+    pub modifier: isize,
+}
+
+pub struct Roll {
+    pub dice: Dice,
+    pub total: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DiceError {
+    Empty,
+    NoDice(String),
+    BadCount(String),
+    BadSides(String),
+    BadModifier(String),
+}
+
+impl fmt::Display for DiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiceError::Empty => write!(f, "empty dice expression"),
+            DiceError::NoDice(s) => write!(f, "no 'd' in dice expression: '{}'", s),
+            DiceError::BadCount(s) => write!(f, "bad dice count: '{}'", s),
+            DiceError::BadSides(s) => write!(f, "bad dice sides: '{}'", s),
+            DiceError::BadModifier(s) => write!(f, "bad dice modifier: '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for DiceError {}
+
+impl Dice {
+    pub fn new(count: usize, sides: usize, modifier: isize) -> Dice {
+        Dice { count, sides, modifier }
+    }
+
+    // `count` d6 without modifier
+    pub fn d6(count: usize) -> Dice {
+        Dice::new(count, DEFAULT_SIDES, 0)
+    }
+
+    pub fn plus(&self, modifier: isize) -> Dice {
+        Dice::new(self.count, self.sides, self.modifier + modifier)
+    }
+
+    // parses "NdS+M", "NdS-M", "NdS", "Nd" and "dS" (a single die)
+    pub fn parse(expr: &str) -> Result<Dice, DiceError> {
+        let expr = expr.trim().to_lowercase();
+
+        if expr.is_empty() {
+            return Err(DiceError::Empty);
+        }
+
+        let (body, modifier) = match expr.find(['+', '-']) {
+            Some(i) => {
+                let m = &expr[i..];
+
+                // digits only, a second sign like "+-3" is a typo
+                if m.len() < 2 || !m[1..].chars().all(|c| c.is_ascii_digit()) {
+                    return Err(DiceError::BadModifier(m.to_string()));
+                }
+
+                let val = m[1..].parse::<isize>().map_err(|_| DiceError::BadModifier(m.to_string()))?;
+                (&expr[..i], if m.starts_with('-') { -val } else { val })
+            }
+            None => (&expr[..], 0),
+        };
+
+        let (count, sides) = body.split_once('d').ok_or(DiceError::NoDice(expr.clone()))?;
+
+        let count = if count.is_empty() {
+            1
+        } else {
+            count.parse::<usize>().map_err(|_| DiceError::BadCount(count.to_string()))?
+        };
+
+        if count > MAX_COUNT {
+            return Err(DiceError::BadCount(count.to_string()));
+        }
+
+        let sides = if sides.is_empty() {
+            DEFAULT_SIDES
+        } else {
+            sides.parse::<usize>().map_err(|_| DiceError::BadSides(sides.to_string()))?
+        };
+
+        if sides == 0 {
+            return Err(DiceError::BadSides(sides.to_string()));
+        }
+
+        Ok(Dice::new(count, sides, modifier))
+    }
+
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> Roll {
+        let sum: usize = (0..self.count).map(|_| rng.random_range(1..=self.sides)).sum();
+
+        Roll {
+            dice: *self,
+            total: (sum as isize + self.modifier).max(0) as usize,
+        }
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+
+        match self.modifier {
+            m if m > 0 => write!(f, "+{}", m),
+            m if m < 0 => write!(f, "{}", m),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Roll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = {}", self.dice, self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_expressions() {
+        assert_eq!(Dice::parse("2d6+1"), Ok(Dice::new(2, 6, 1)));
+        assert_eq!(Dice::parse("1d6-3"), Ok(Dice::new(1, 6, -3)));
+        assert_eq!(Dice::parse("3d8"), Ok(Dice::new(3, 8, 0)));
+        assert_eq!(Dice::parse("2d"), Ok(Dice::new(2, 6, 0)));
+        assert_eq!(Dice::parse("2d+1"), Ok(Dice::new(2, 6, 1)));
+        assert_eq!(Dice::parse("d20"), Ok(Dice::new(1, 20, 0)));
+        assert_eq!(Dice::parse(" 4D6 "), Ok(Dice::new(4, 6, 0)));
+        assert_eq!(Dice::parse("100d6"), Ok(Dice::new(100, 6, 0)));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(Dice::parse(""), Err(DiceError::Empty));
+        assert_eq!(Dice::parse("3"), Err(DiceError::NoDice("3".to_string())));
+        assert_eq!(Dice::parse("-3"), Err(DiceError::NoDice("-3".to_string())));
+        assert_eq!(Dice::parse("1d6+-3"), Err(DiceError::BadModifier("+-3".to_string())));
+        assert_eq!(Dice::parse("1d6+"), Err(DiceError::BadModifier("+".to_string())));
+        assert_eq!(Dice::parse("xd6"), Err(DiceError::BadCount("x".to_string())));
+        assert_eq!(Dice::parse("2d0"), Err(DiceError::BadSides("0".to_string())));
+        assert_eq!(Dice::parse("2dx"), Err(DiceError::BadSides("x".to_string())));
+        assert_eq!(Dice::parse("101d6"), Err(DiceError::BadCount("101".to_string())));
+        assert_eq!(Dice::parse("99999999999d6"), Err(DiceError::BadCount("99999999999".to_string())));
+    }
+
+    #[test]
+    fn printing_round_trips() {
+        for expr in ["2d6+1", "1d6-3", "3d8"] {
+            assert_eq!(Dice::parse(expr).unwrap().to_string(), expr);
+        }
+    }
+}
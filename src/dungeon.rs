@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::mem;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::config::MAX_MAP_SIZE;
+use crate::hidden::{HMatrix, Trap};
+use crate::monster::Monster;
+use crate::room::RMatrix;
+use crate::{AMatrix, GameVars, VMatrix};
+
+// cells the stairs of a floor stand in, always on the middle tile of the room
+pub struct Stairs {
+    pub down: (usize, usize),
+    // none on the floor the run started on
+    pub up: Option<(usize, usize)>,
+}
+
+// a floor the hero is not on, kept exactly as it was left
+pub struct Floor {
+    map: AMatrix,
+    visit_map: VMatrix,
+    rooms: RMatrix,
+    secrets: HMatrix,
+    traps: Vec<Trap>,
+    monsters: Vec<Monster>,
+    stairs: Stairs,
+}
+
+// floors left behind, by level
+pub type Floors = HashMap<usize, Floor>;
+
+impl Floor {
+    pub fn rooms_explored(&self) -> usize {
+        self.visit_map.entered_count()
+    }
+}
+
+impl GameVars {
+    fn swap_floor(&mut self, floor: &mut Floor) {
+        mem::swap(&mut self.map, &mut floor.map);
+        mem::swap(&mut self.visit_map, &mut floor.visit_map);
+        mem::swap(&mut self.rooms, &mut floor.rooms);
+        mem::swap(&mut self.secrets, &mut floor.secrets);
+        mem::swap(&mut self.traps, &mut floor.traps);
+        mem::swap(&mut self.monsters, &mut floor.monsters);
+        mem::swap(&mut self.stairs, &mut floor.stairs);
+    }
+
+    // room furthest from the start through known doors, where the way down is
+    pub fn place_stairs(&mut self) {
+        // the start room itself comes first, the list is never empty
+        self.stairs.down = self.rooms_by_distance().last().unwrap().0;
+    }
+
+    pub fn stairs_tile(&self, (row, col): (usize, usize)) -> (usize, usize) {
+        (col * self.base + self.base / 2, row * self.base + self.base / 2)
+    }
+
+    fn on_stairs(&self, cell: Option<(usize, usize)>) -> bool {
+        cell.map(|c| self.stairs_tile(c)) == Some((self.hero_pos_x, self.hero_pos_y))
+    }
+
+    pub fn descend(&mut self) {
+        if !self.on_stairs(Some(self.stairs.down)) {
+            self.log("There are no stairs down here.".to_string());
+            return;
+        }
+
+        let above = self.level;
+        let below = above + 1;
+        self.level = below;
+
+        match self.floors.remove(&below) {
+            Some(mut floor) => {
+                self.swap_floor(&mut floor);
+                self.floors.insert(above, floor);
+                self.log(format!("You go back down to level {}.", below));
+            },
+            None => {
+                self.log(format!("You descend to level {}.", below));
+                self.new_floor(above);
+            },
+        }
+
+        (self.hero_pos_x, self.hero_pos_y) = self.stairs_tile(self.stairs.up.unwrap());
+        self.leave_floor();
+    }
+
+    pub fn ascend(&mut self) {
+        if !self.on_stairs(self.stairs.up) {
+            self.log("There are no stairs up here.".to_string());
+            return;
+        }
+
+        let above = self.level - 1;
+        let mut floor = self.floors.remove(&above).unwrap();
+
+        self.swap_floor(&mut floor);
+        self.floors.insert(self.level, floor);
+
+        self.level = above;
+        (self.hero_pos_x, self.hero_pos_y) = self.stairs_tile(self.stairs.down);
+        self.log(format!("You climb back up to level {}.", above));
+        self.leave_floor();
+    }
+
+    // a new, a bit larger floor for the current level; the one the hero
+    // comes from is stored away as the level above
+    fn new_floor(&mut self, above: usize) {
+        let w = (self.map.matrix.cols + 1).min(MAX_MAP_SIZE);
+        let h = (self.map.matrix.rows + 1).min(MAX_MAP_SIZE);
+
+        let mut floor = Floor {
+            map: AMatrix::new(w, h, self.map.block_chance, StdRng::from_rng(&mut self.rng)),
+            visit_map: VMatrix::new(w, h, w/2, h/2),
+            rooms: RMatrix::new(w, h, h/2, w/2, &mut self.rng),
+            secrets: HMatrix::new(w, h),
+            traps: Vec::new(),
+            monsters: Vec::new(),
+            stairs: Stairs { down: (h/2, w/2), up: Some((h/2, w/2)) },
+        };
+
+        self.swap_floor(&mut floor);
+        self.floors.insert(above, floor);
+
+        self.hero_pos_x = w/2 * self.base + self.base/2;
+        self.hero_pos_y = h/2 * self.base + self.base/2;
+
+        self.fill_floor();
+    }
+
+    // what every fresh floor gets once the hero stands in its start room,
+    // the first one of a run included
+    pub fn fill_floor(&mut self) {
+        self.set_st_hero_pos();
+        self.hide_features();
+
+        // the arena is the room furthest away, whatever the rest looks like
+        let arena = if self.is_boss_floor() {
+            self.add_arena()
+        } else {
+            self.add_vault();
+            None
+        };
+
+        self.spawn_monsters();
+        self.place_stairs();
+
+        if self.is_boss_floor() {
+            self.stairs.down = arena.unwrap_or(self.stairs.down);
+            self.spawn_boss(self.stairs.down);
+
+            let boss = self.monsters.last().unwrap().kind().name;
+
+            match arena {
+                Some(_) => self.log(format!("The air is heavy here, the {} guards the way down.", boss)),
+                None => self.log(format!("The air is heavy here, the {} guards the stairs down. The floor is too small for an arena.", boss)),
+            }
+        }
+    }
+
+    // whatever was open on the old floor stays there
+    fn leave_floor(&mut self) {
+        self.shop = None;
+        self.travel_cursor = None;
+        self.look_cursor = None;
+        self.end_turn();
+    }
+
+    pub fn stairs_at(&self, x: usize, y: usize) -> Option<char> {
+        if self.stairs_tile(self.stairs.down) == (x, y) {
+            Some('>')
+        } else if self.stairs.up.map(|c| self.stairs_tile(c)) == Some((x, y)) {
+            Some('<')
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::palette::Hue;
+use crate::GameVars;
+
+// strongest a poison gets, however many doses the hero takes
+static MAX_POISON: usize = 3;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Effect {
+    // loses hp every turn, more with every dose
+    Poison,
+    // the monsters only move every other turn
+    Haste,
+    // the monsters move twice a turn
+    Slow,
+    // fights and dodges worse
+    Blind,
+    // gains hp every turn
+    Regen,
+}
+
+// an effect on the hero with the turns it has left
+pub struct Status {
+    pub effect: Effect,
+    pub turns: usize,
+    // hp a turn for poison and regen
+    pub power: usize,
+}
+
+impl Effect {
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Poison => "poison",
+            Effect::Haste => "haste",
+            Effect::Slow => "slow",
+            Effect::Blind => "blindness",
+            Effect::Regen => "regeneration",
+        }
+    }
+
+    // short enough for all of them to fit under the stats
+    pub fn badge(self) -> &'static str {
+        match self {
+            Effect::Poison => "PO",
+            Effect::Haste => "HA",
+            Effect::Slow => "SL",
+            Effect::Blind => "BL",
+            Effect::Regen => "RG",
+        }
+    }
+
+    pub fn hue(self) -> Hue {
+        match self {
+            Effect::Haste | Effect::Regen => Hue::Info,
+            _ => Hue::Trap,
+        }
+    }
+
+    // turns a single dose lasts
+    fn turns(self) -> usize {
+        match self {
+            Effect::Poison => 5,
+            Effect::Haste => 10,
+            Effect::Slow => 8,
+            Effect::Blind => 6,
+            Effect::Regen => 12,
+        }
+    }
+
+    // attack, armor and speed on top of the hero's own
+    fn modifiers(self) -> (isize, isize, isize) {
+        match self {
+            Effect::Haste => (0, 0, 2),
+            Effect::Slow => (0, -1, -2),
+            Effect::Blind => (-2, -1, 0),
+            Effect::Poison | Effect::Regen => (0, 0, 0),
+        }
+    }
+
+    // haste and slow cancel each other out
+    fn opposite(self) -> Option<Effect> {
+        match self {
+            Effect::Haste => Some(Effect::Slow),
+            Effect::Slow => Some(Effect::Haste),
+            _ => None,
+        }
+    }
+
+    fn start_message(self) -> &'static str {
+        match self {
+            Effect::Poison => "You are poisoned!",
+            Effect::Haste => "You feel yourself speed up.",
+            Effect::Slow => "Your limbs grow heavy.",
+            Effect::Blind => "You can't see a thing!",
+            Effect::Regen => "A warmth spreads through your wounds.",
+        }
+    }
+
+    fn end_message(self) -> &'static str {
+        match self {
+            Effect::Poison => "The poison wears off.",
+            Effect::Haste => "You slow down again.",
+            Effect::Slow => "You can move freely again.",
+            Effect::Blind => "You can see again.",
+            Effect::Regen => "The warmth fades.",
+        }
+    }
+}
+
+fn apply(stat: usize, bonus: isize) -> usize {
+    (stat as isize + bonus).max(0) as usize
+}
+
+impl GameVars {
+    pub fn has_effect(&self, effect: Effect) -> bool {
+        self.effects.iter().any(|s| s.effect == effect)
+    }
+
+    // one more dose; poison gets stronger, regeneration lasts longer and the
+    // rest start over from a full duration
+    pub fn afflict(&mut self, effect: Effect) {
+        if let Some(idx) = effect.opposite().and_then(|o| self.effects.iter().position(|s| s.effect == o)) {
+            let other = self.effects.remove(idx).effect;
+            self.log(format!("The {} and the {} cancel each other out.", effect.name(), other.name()));
+            return;
+        }
+
+        let Some(status) = self.effects.iter_mut().find(|s| s.effect == effect) else {
+            self.effects.push(Status { effect, turns: effect.turns(), power: 1 });
+            self.log(effect.start_message().to_string());
+            return;
+        };
+
+        match effect {
+            Effect::Poison => {
+                status.power = (status.power + 1).min(MAX_POISON);
+                status.turns = status.turns.max(effect.turns());
+                self.log("The poison grows stronger.".to_string());
+            },
+            Effect::Regen => {
+                status.turns += effect.turns();
+                self.log("The warmth will last longer.".to_string());
+            },
+            _ => {
+                status.turns = status.turns.max(effect.turns());
+                self.log(format!("Your {} lasts longer.", effect.name()));
+            },
+        }
+    }
+
+    // times the monsters move for one hero action; haste and slow never
+    // stack, one cancels the other
+    pub fn monster_moves(&self) -> usize {
+        if self.has_effect(Effect::Haste) {
+            usize::from(!self.turn.is_multiple_of(2))
+        } else if self.has_effect(Effect::Slow) {
+            2
+        } else {
+            1
+        }
+    }
+
+    // once a turn, after the monsters moved
+    pub fn tick_effects(&mut self) {
+        for idx in 0..self.effects.len() {
+            // poison can kill, a later regen must not bring the hero back
+            if self.is_dead() {
+                return;
+            }
+
+            let Status { effect, power, .. } = self.effects[idx];
+
+            match effect {
+                Effect::Poison => self.hurt(power, "the poison"),
+                Effect::Regen => self.hp = (self.hp + power).min(self.max_hp),
+                _ => {},
+            }
+
+            self.effects[idx].turns -= 1;
+        }
+
+        let (done, left): (Vec<Status>, Vec<Status>) = self.effects.drain(..).partition(|s| s.turns == 0);
+        self.effects = left;
+
+        for status in done {
+            self.log(status.effect.end_message().to_string());
+        }
+    }
+
+    fn bonus(&self, pick: fn((isize, isize, isize)) -> isize) -> isize {
+        self.effects.iter().map(|s| pick(s.effect.modifiers())).sum()
+    }
+
+    // stats with the effects on top, what every roll and check uses
+    pub fn attack_now(&self) -> usize {
+        apply(self.attack, self.bonus(|m| m.0))
+    }
+
+    pub fn armor_now(&self) -> usize {
+        apply(self.armor, self.bonus(|m| m.1))
+    }
+
+    pub fn speed_now(&self) -> usize {
+        apply(self.speed, self.bonus(|m| m.2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Settings;
+    use crate::{Game, RunSetup};
+
+    fn hero() -> GameVars {
+        Game::new(0, 0, &RunSetup::new(1, 1, None, None, Settings::default())).vars
+    }
+
+    #[test]
+    fn doses_stack() {
+        let mut vars = hero();
+
+        for _ in 0..5 {
+            vars.afflict(Effect::Poison);
+        }
+        vars.afflict(Effect::Regen);
+        vars.afflict(Effect::Regen);
+
+        assert_eq!(vars.effects.len(), 2);
+        assert_eq!((vars.effects[0].power, vars.effects[0].turns), (MAX_POISON, 5));
+        assert_eq!(vars.effects[1].turns, 24);
+
+        vars.afflict(Effect::Haste);
+        vars.afflict(Effect::Slow);
+        assert!(!vars.has_effect(Effect::Haste) && !vars.has_effect(Effect::Slow));
+    }
+
+    #[test]
+    fn effects_expire() {
+        let mut vars = hero();
+        vars.afflict(Effect::Blind);
+
+        for _ in 0..5 {
+            vars.tick_effects();
+        }
+        assert!(vars.has_effect(Effect::Blind));
+
+        vars.tick_effects();
+        assert!(!vars.has_effect(Effect::Blind));
+        assert_eq!(vars.log.last().map(String::as_str), Some("You can see again."));
+    }
+
+    #[test]
+    fn regen_does_not_revive() {
+        let mut vars = hero();
+        vars.hp = 1;
+        vars.afflict(Effect::Poison);
+        vars.afflict(Effect::Regen);
+
+        vars.tick_effects();
+        assert_eq!(vars.hp, 0);
+    }
+
+    #[test]
+    fn speed_changes_turn_order() {
+        let mut vars = hero();
+        let moves = |vars: &mut GameVars| (0..4).map(|t| { vars.turn = t; vars.monster_moves() }).sum::<usize>();
+
+        assert_eq!(moves(&mut vars), 4);
+
+        vars.afflict(Effect::Haste);
+        assert_eq!(moves(&mut vars), 2);
+
+        vars.effects.clear();
+        vars.afflict(Effect::Slow);
+        assert_eq!(moves(&mut vars), 8);
+    }
+}
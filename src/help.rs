@@ -0,0 +1,158 @@
+use crossterm::event::{Event, KeyCode};
+use crossterm::terminal;
+use crate::input::{key_name, Action, BINDINGS};
+use crate::monster::MONSTERS;
+use crate::widgets::{pressed, Outcome};
+use crate::{Game, GameVars, Layer, RenderableContent, TerminalImage};
+
+static INTRO: [&str; 15] = [
+    "Find your way through the dungeon, one room at a time.",
+    "Walk into a monster to attack it, dice decide every blow.",
+    "Rooms hold treasure, shrines, merchants, traps and monster lairs.",
+    "Search for hidden traps and secret doors, drink potions to heal",
+    "and trade when you meet a merchant. Exploring walks to the",
+    "nearest unknown room, travel to a known one. Stairs lead down",
+    "to harder floors, and back up to the ones you left.",
+    "Poison, haste and other effects wear off after a few turns,",
+    "the letters under your stats tell which ones are on you.",
+    "Hasted you act twice before the monsters do, slowed they do.",
+    "On hard runs your torch burns down: once it is out you forget",
+    "every room you leave, so carry a spare.",
+    "",
+    "Click a tile to walk there, hover over it to hear what it is.",
+    "The deeper you get the better your score.",
+];
+
+// the help sections, each one starts on a new page
+static SECTIONS: [&str; 3] = ["HOW TO PLAY", "KEYS", "MAP"];
+pub static KEYS_SECTION: usize = 1;
+
+// tallest the help window gets, smaller terminals get more pages
+static MAX_ROWS: usize = 15;
+
+fn description(action: Action) -> &'static str {
+    match action {
+        Action::Up => "move north, or choose the entry above",
+        Action::Down => "move south, or choose the entry below",
+        Action::Left => "move west",
+        Action::Right => "move east",
+        Action::Confirm => "pick the chosen entry or room",
+        Action::Cancel => "close, stop picking, or abandon the run",
+        Action::Switch => "buy or sell in a shop, next sight when looking",
+        Action::Trade => "trade with a merchant",
+        Action::Drink => "drink a potion",
+        Action::Search => "search the room for hidden things",
+        Action::Explore => "walk to the nearest unknown room",
+        Action::Travel => "pick a known room to walk to",
+        Action::Look => "look around with a cursor",
+        Action::Descend => "take the stairs down",
+        Action::Ascend => "take the stairs back up",
+        Action::Camera => "change how the map follows you",
+        Action::Help => "this help",
+        Action::TravelTo(_, _) => "walk to a tile",
+        Action::Quit => "abandon the run",
+    }
+}
+
+// one line per binding, so a changed binding shows up here at once
+fn key_lines() -> Vec<String> {
+    BINDINGS.iter().map(|(key, action)| format!("{:<8}{}", key_name(*key), description(*action))).collect()
+}
+
+// glyphs as the current tileset draws them
+fn legend_lines(game: &GameVars) -> Vec<String> {
+    let mut lines: Vec<String> = game.tileset.legend().iter().map(|(c, what)| format!("{}  {}", c, what)).collect();
+
+    lines.push("@  you".to_string());
+    lines.extend(MONSTERS.iter().map(|m| format!("{}  {}", m.glyph, m.name)));
+    lines.push(">  stairs down".to_string());
+    lines.push("<  stairs up".to_string());
+    lines.push("^  trap you found".to_string());
+    lines.push("X  where travel takes you".to_string());
+    lines.push("[] look cursor".to_string());
+    lines
+}
+
+fn section_lines(game: &GameVars, section: usize) -> Vec<String> {
+    match section {
+        0 => INTRO.iter().map(|l| l.to_string()).collect(),
+        1 => key_lines(),
+        _ => legend_lines(game),
+    }
+}
+
+// pages of a fixed size, the section title on top of each
+pub struct HelpWindowContent {
+    pages: Vec<Vec<String>>,
+    page: usize,
+}
+
+impl HelpWindowContent {
+    pub fn new(game: &GameVars, rows: usize, section: usize) -> HelpWindowContent {
+        // title, blank line and footer take three rows
+        let per_page = rows.saturating_sub(3).max(1);
+        let mut pages = Vec::new();
+        let mut first = Vec::new();
+
+        for (i, title) in SECTIONS.iter().enumerate() {
+            first.push(pages.len());
+
+            for chunk in section_lines(game, i).chunks(per_page) {
+                let mut page = vec![title.to_string(), String::new()];
+                page.extend(chunk.iter().cloned());
+                pages.push(page);
+            }
+        }
+
+        HelpWindowContent { page: first[section], pages }
+    }
+}
+
+impl RenderableContent for HelpWindowContent {
+    fn render(&self, _game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let clip = |line: &str| line.chars().take(cols - 2).collect::<String>();
+        let mut imgs: Vec<TerminalImage> = self.pages[self.page].iter().enumerate()
+            .map(|(y, line)| TerminalImage::with_text(clip(line), 1, y as isize))
+            .collect();
+
+        let footer = format!("page {}/{}  arrows: turn pages  esc: close", self.page + 1, self.pages.len());
+        imgs.push(TerminalImage::with_text(clip(&footer), 1, rows as isize - 1));
+        imgs
+    }
+
+    fn handle(&mut self, event: &Event) -> Option<Outcome> {
+        match pressed(event)? {
+            KeyCode::Right | KeyCode::Down | KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.page = (self.page + 1).min(self.pages.len() - 1);
+            },
+            KeyCode::Left | KeyCode::Up | KeyCode::PageUp => self.page = self.page.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('?') => return Some(Outcome::Closed),
+            _ => {},
+        }
+        None
+    }
+}
+
+impl Game {
+    // help over whatever is on screen, as tall as the terminal allows
+    pub fn open_help(&mut self, section: usize) {
+        // read out from the section on, there is no paging in linear mode
+        if self.screen.linear {
+            for (i, title) in SECTIONS.iter().enumerate().skip(section) {
+                self.vars.log(format!("{}:", title));
+
+                for line in section_lines(&self.vars, i).into_iter().filter(|l| !l.is_empty()) {
+                    self.vars.log(line);
+                }
+            }
+            return;
+        }
+
+        let term_rows = terminal::size().map(|(_, rows)| rows as usize).unwrap_or(MAX_ROWS);
+        let rows = term_rows.saturating_sub(4).clamp(4, MAX_ROWS);
+        let pos_y = term_rows.saturating_sub(rows + 1).clamp(1, 6);
+        let content = HelpWindowContent::new(&self.vars, rows, section);
+
+        self.screen.add_window(content, rows, 69, 2, pos_y, true, ' ').named("help").on(Layer::Modal);
+    }
+}
@@ -0,0 +1,224 @@
+use rand::Rng;
+use crate::dice::Dice;
+use crate::effects::Effect;
+use crate::{Dir, GameVars, Matrix};
+
+static SECRET_DOOR_CHANCE: usize = 8;
+static TRAP_CHANCE: usize = 15;
+static SEARCH_DIFFICULTY: usize = 6;
+static TELEPORT_TRIES: usize = 50;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum TrapKind {
+    Spikes,
+    Teleport,
+    Alarm,
+    Needle,
+    Gas,
+}
+
+impl TrapKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrapKind::Spikes => "spike trap",
+            TrapKind::Teleport => "teleport trap",
+            TrapKind::Alarm => "alarm trap",
+            TrapKind::Needle => "poison needle",
+            TrapKind::Gas => "gas trap",
+        }
+    }
+}
+
+pub struct Trap {
+    pub kind: TrapKind,
+    pub pos_x: usize,
+    pub pos_y: usize,
+    pub found: bool,
+}
+
+// door bits (same layout as in AMatrix) which are open but not discovered yet
+pub struct HMatrix {
+    matrix: Matrix,
+}
+
+impl HMatrix {
+    pub fn new(w: usize, h: usize) -> HMatrix {
+        HMatrix {
+            matrix: Matrix::new(h, w, 0)
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> usize {
+        self.matrix.get(row, col)
+    }
+
+    fn hide(&mut self, row: usize, col: usize, dir: Dir) {
+        let v = self.get(row, col);
+        self.matrix.set(row, col, v | 1 << dir as usize);
+    }
+
+    pub fn reveal(&mut self, row: usize, col: usize, dir: Dir) {
+        let v = self.get(row, col);
+        self.matrix.set(row, col, v & !(1 << dir as usize));
+    }
+}
+
+impl GameVars {
+    // picks secret doors and floor traps, the starting room is left alone
+    pub fn hide_features(&mut self) {
+        let rows = self.map.matrix.rows;
+        let cols = self.map.matrix.cols;
+        let start = self.hero_cell();
+
+        for row in 1..rows-1 {
+            for col in 1..cols-1 {
+                if (row, col) == start {
+                    continue;
+                }
+
+                for dir in [Dir::Right, Dir::Down] {
+                    let Some(other) = self.map.get_neighbor(row, col, dir) else { continue };
+                    let open = self.map.get(row, col) & (1 << dir as usize) != 0;
+
+                    if open && other != start && self.rng.random_range(0..100) < SECRET_DOOR_CHANCE {
+                        self.secrets.hide(row, col, dir);
+                        self.secrets.hide(other.0, other.1, dir.opposite());
+                    }
+                }
+
+                if self.map.get(row, col) != 0 && self.rng.random_range(0..100) < TRAP_CHANCE {
+                    self.place_trap(row, col);
+                }
+            }
+        }
+    }
+
+    fn place_trap(&mut self, row: usize, col: usize) {
+        let kind = match self.rng.random_range(0..5) {
+            0 => TrapKind::Spikes,
+            1 => TrapKind::Teleport,
+            2 => TrapKind::Alarm,
+            3 => TrapKind::Needle,
+            _ => TrapKind::Gas,
+        };
+
+        // somewhere on the floor around the middle of the room
+        let x = col * self.base + self.base / 2 + self.rng.random_range(0..3) - 1;
+        let y = row * self.base + self.base / 2 + self.rng.random_range(0..3) - 1;
+
+        if self.is_walkable(x, y) && self.trap_at(x, y).is_none() {
+            self.traps.push(Trap { kind, pos_x: x, pos_y: y, found: false });
+        }
+    }
+
+    // doors of the cell as seen by the hero, undiscovered secret doors are walls
+    pub fn visible_doors(&self, row: usize, col: usize) -> usize {
+        self.map.get(row, col) & !self.secrets.get(row, col)
+    }
+
+    pub fn trap_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.traps.iter().position(|t| t.pos_x == x && t.pos_y == y)
+    }
+
+    // looks for secret doors and traps in the current room, takes a turn
+    pub fn search(&mut self) {
+        let (row, col) = self.hero_cell();
+        let mut found = false;
+
+        self.log("You search the room...".to_string());
+
+        for dir in [Dir::Up, Dir::Right, Dir::Down, Dir::Left] {
+            if self.secrets.get(row, col) & (1 << dir as usize) == 0 {
+                continue;
+            }
+
+            if self.check("Search", self.speed_now(), SEARCH_DIFFICULTY) {
+                self.secrets.reveal(row, col, dir);
+
+                if let Some((n_row, n_col)) = self.map.get_neighbor(row, col, dir) {
+                    self.secrets.reveal(n_row, n_col, dir.opposite());
+                }
+
+                self.log("You find a secret door!".to_string());
+                found = true;
+            }
+        }
+
+        for idx in 0..self.traps.len() {
+            let t = &self.traps[idx];
+
+            if t.found || (t.pos_y / self.base, t.pos_x / self.base) != (row, col) {
+                continue;
+            }
+
+            if self.check("Search", self.speed_now(), SEARCH_DIFFICULTY) {
+                self.traps[idx].found = true;
+                self.log("You find a hidden trap.".to_string());
+                found = true;
+            }
+        }
+
+        if !found {
+            self.log("You find nothing.".to_string());
+        }
+
+        self.end_turn();
+    }
+
+    // hidden trap under the hero goes off, known ones are stepped over
+    pub fn spring_trap(&mut self) {
+        let Some(idx) = self.trap_at(self.hero_pos_x, self.hero_pos_y) else { return };
+
+        if self.traps[idx].found {
+            self.log("You step carefully around the trap.".to_string());
+            return;
+        }
+
+        self.traps[idx].found = true;
+
+        match self.traps[idx].kind {
+            TrapKind::Spikes => {
+                self.log("Spikes shoot up from the floor!".to_string());
+                let dmg = Dice::d6(1).plus(self.level as isize).roll(&mut self.rng).total;
+                self.hurt(dmg, "the spikes");
+            },
+            TrapKind::Teleport => {
+                self.log("A glowing rune flashes and the world spins!".to_string());
+                self.teleport();
+            },
+            TrapKind::Alarm => {
+                self.log("A loud bell rings through the dungeon!".to_string());
+
+                for m in self.monsters.iter_mut() {
+                    m.awake = true;
+                }
+            },
+            TrapKind::Needle => {
+                self.log("A needle jabs into your foot!".to_string());
+                self.afflict(Effect::Poison);
+            },
+            TrapKind::Gas => {
+                self.log("A cloud of gas bursts into your face!".to_string());
+                self.afflict(Effect::Blind);
+            },
+        }
+    }
+
+    // a random free room, or nowhere if none turns up after a few tries
+    fn teleport(&mut self) {
+        for _ in 0..TELEPORT_TRIES {
+            let (row, col) = self.map.random_cell();
+            let x = col * self.base + self.base / 2;
+            let y = row * self.base + self.base / 2;
+
+            if self.is_walkable(x, y) && self.monster_at(x, y).is_none() {
+                self.hero_pos_x = x;
+                self.hero_pos_y = y;
+                self.enter_room();
+                return;
+            }
+        }
+
+        self.log("The world settles back where it was.".to_string());
+    }
+}
@@ -0,0 +1,122 @@
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEventKind};
+
+// everything the player can ask for, independent of the key pressed;
+// what an action does depends on what is currently open (shop, travel map...)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Cancel,
+    Switch,
+    Trade,
+    Drink,
+    Search,
+    Explore,
+    Travel,
+    Look,
+    Descend,
+    Ascend,
+    Camera,
+    Help,
+    // walk to a tile picked with the mouse
+    TravelTo(usize, usize),
+    Quit,
+}
+
+pub static ACTIONS: [(Action, &str); 18] = [
+    (Action::Up, "up"),
+    (Action::Down, "down"),
+    (Action::Left, "left"),
+    (Action::Right, "right"),
+    (Action::Confirm, "confirm"),
+    (Action::Cancel, "cancel"),
+    (Action::Switch, "switch"),
+    (Action::Trade, "trade"),
+    (Action::Drink, "drink"),
+    (Action::Search, "search"),
+    (Action::Explore, "explore"),
+    (Action::Travel, "travel"),
+    (Action::Look, "look"),
+    (Action::Descend, "descend"),
+    (Action::Ascend, "ascend"),
+    (Action::Camera, "camera"),
+    (Action::Help, "help"),
+    (Action::Quit, "quit"),
+];
+
+pub static BINDINGS: [(KeyCode, Action); 18] = [
+    (KeyCode::Up, Action::Up),
+    (KeyCode::Down, Action::Down),
+    (KeyCode::Left, Action::Left),
+    (KeyCode::Right, Action::Right),
+    (KeyCode::Enter, Action::Confirm),
+    (KeyCode::Esc, Action::Cancel),
+    (KeyCode::Tab, Action::Switch),
+    (KeyCode::Char('t'), Action::Trade),
+    (KeyCode::Char('u'), Action::Drink),
+    (KeyCode::Char('s'), Action::Search),
+    (KeyCode::Char('o'), Action::Explore),
+    (KeyCode::Char('g'), Action::Travel),
+    (KeyCode::Char('l'), Action::Look),
+    (KeyCode::Char('>'), Action::Descend),
+    (KeyCode::Char('<'), Action::Ascend),
+    (KeyCode::Char('v'), Action::Camera),
+    (KeyCode::Char('?'), Action::Help),
+    (KeyCode::Char('q'), Action::Quit),
+];
+
+impl Action {
+    // name followed by arguments, if any: "up", "travel-to 12 30"
+    pub fn name(&self) -> String {
+        match self {
+            Action::TravelTo(x, y) => format!("travel-to {} {}", x, y),
+            _ => ACTIONS.iter().find(|(a, _)| a == self).map(|(_, n)| n.to_string()).unwrap(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        let mut parts = name.split(' ');
+
+        match parts.next()? {
+            "travel-to" => Some(Action::TravelTo(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?)),
+            first => ACTIONS.iter().find(|(_, n)| *n == first).map(|(a, _)| *a),
+        }
+    }
+}
+
+// "up", "enter", "q"
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(c) => c.to_string(),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
+
+pub fn map_event(event: &Event) -> Option<Action> {
+    match event {
+        Event::Key(KeyEvent{code, kind: KeyEventKind::Press, ..}) => {
+            BINDINGS.iter().find(|(k, _)| k == code).map(|(_, a)| *a)
+        },
+        _ => None,
+    }
+}
+
+// a key press or a click, mouse movement doesn't count
+pub fn is_press(event: &Event) -> bool {
+    match event {
+        Event::Key(KeyEvent{kind: KeyEventKind::Press, ..}) => true,
+        Event::Mouse(m) => matches!(m.kind, MouseEventKind::Down(MouseButton::Left)),
+        _ => false,
+    }
+}
+
+pub fn wait_press() {
+    while let Ok(event) = read() {
+        if is_press(&event) {
+            return;
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::dice::Dice;
+use crate::effects::Effect;
+
+pub struct ItemKind {
+    pub name: &'static str,
+    pub price: usize,
+    pub attack: usize,
+    pub armor: usize,
+    pub speed: usize,
+    pub heal: &'static str,
+    // put on the hero when drunk
+    pub effect: Option<Effect>,
+    // a spare, lit when the one in hand burns out
+    pub torch: bool,
+    pub min_level: usize,
+}
+
+// carried gear gives its bonus for as long as it stays in the inventory,
+// potions (items with `heal` dice or an effect) are consumed on use
+pub static ITEMS: [ItemKind; 9] = [
+    ItemKind{ name: "healing potion", price: 8, attack: 0, armor: 0, speed: 0, heal: "2d6", effect: None, torch: false, min_level: 1 },
+    ItemKind{ name: "dagger", price: 15, attack: 1, armor: 0, speed: 0, heal: "", effect: None, torch: false, min_level: 1 },
+    ItemKind{ name: "leather armor", price: 20, attack: 0, armor: 1, speed: 0, heal: "", effect: None, torch: false, min_level: 1 },
+    ItemKind{ name: "boots", price: 20, attack: 0, armor: 0, speed: 1, heal: "", effect: None, torch: false, min_level: 1 },
+    ItemKind{ name: "sword", price: 40, attack: 2, armor: 0, speed: 0, heal: "", effect: None, torch: false, min_level: 2 },
+    ItemKind{ name: "chain mail", price: 50, attack: 0, armor: 2, speed: 0, heal: "", effect: None, torch: false, min_level: 3 },
+    ItemKind{ name: "regeneration potion", price: 12, attack: 0, armor: 0, speed: 0, heal: "", effect: Some(Effect::Regen), torch: false, min_level: 1 },
+    ItemKind{ name: "haste potion", price: 15, attack: 0, armor: 0, speed: 0, heal: "", effect: Some(Effect::Haste), torch: false, min_level: 2 },
+    ItemKind{ name: "torch", price: 6, attack: 0, armor: 0, speed: 0, heal: "", effect: None, torch: true, min_level: 1 },
+];
+
+impl ItemKind {
+    pub fn heal_dice(&self) -> Option<Dice> {
+        if self.heal.is_empty() {
+            None
+        } else {
+            Some(Dice::parse(self.heal).unwrap())
+        }
+    }
+
+    pub fn is_potion(&self) -> bool {
+        !self.heal.is_empty() || self.effect.is_some()
+    }
+
+    pub fn sell_price(&self) -> usize {
+        self.price / 2
+    }
+}
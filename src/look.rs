@@ -0,0 +1,60 @@
+use crate::{Dir, GameVars};
+
+impl GameVars {
+    pub fn start_look(&mut self) {
+        self.look_cursor = Some((self.hero_pos_x, self.hero_pos_y));
+        self.log("Look around (arrows: move, tab: next thing, esc: done).".to_string());
+    }
+
+    pub fn move_look_cursor(&mut self, dir: Dir) {
+        let Some((x, y)) = self.look_cursor else { return };
+        let w = self.map.matrix.cols * self.base;
+        let h = self.map.matrix.rows * self.base;
+
+        let next = match dir {
+            Dir::Up if y > 0 => (x, y - 1),
+            Dir::Down if y + 1 < h => (x, y + 1),
+            Dir::Left if x > 0 => (x - 1, y),
+            Dir::Right if x + 1 < w => (x + 1, y),
+            _ => return,
+        };
+
+        self.look_at(next);
+    }
+
+    // monsters and found traps in known rooms, closest first
+    fn interesting_tiles(&self) -> Vec<(usize, usize)> {
+        let known = |&(x, y): &(usize, usize)| self.visit_map.known(y / self.base, x / self.base);
+
+        let mut tiles: Vec<(usize, usize)> = self.monsters.iter().map(|m| (m.pos_x, m.pos_y))
+            .chain(self.traps.iter().filter(|t| t.found).map(|t| (t.pos_x, t.pos_y)))
+            .chain([Some(self.stairs.down), self.stairs.up].into_iter().flatten().map(|c| self.stairs_tile(c)))
+            .filter(known)
+            .collect();
+
+        tiles.sort_by_key(|&(x, y)| x.abs_diff(self.hero_pos_x) + y.abs_diff(self.hero_pos_y));
+        tiles
+    }
+
+    pub fn look_next(&mut self) {
+        let Some(cursor) = self.look_cursor else { return };
+        let tiles = self.interesting_tiles();
+
+        if tiles.is_empty() {
+            self.log("Nothing of interest in sight.".to_string());
+            return;
+        }
+
+        let next = match tiles.iter().position(|&t| t == cursor) {
+            Some(i) => tiles[(i + 1) % tiles.len()],
+            None => tiles[0],
+        };
+
+        self.look_at(next);
+    }
+
+    fn look_at(&mut self, tile: (usize, usize)) {
+        self.look_cursor = Some(tile);
+        self.log(self.describe(tile.0, tile.1));
+    }
+}
@@ -1,14 +1,62 @@
 use std::io;
 use crossterm::queue;
 use std::io::{Write, stdout};
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use crossterm::{ExecutableCommand, cursor};
-use crossterm::terminal::{ClearType, Clear, enable_raw_mode, disable_raw_mode};
-use crossterm::event::{read, Event, KeyEvent, KeyCode};
-use crossterm::execute;
+use crossterm::event::{read, Event, MouseButton, MouseEventKind};
 use crossterm::style;
-use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
-
+use crossterm::terminal::{Clear, ClearType};
+
+mod ai;
+mod access;
+mod camera;
+mod cli;
+mod config;
+mod crash;
+mod dice;
+mod dungeon;
+mod effects;
+mod help;
+mod hidden;
+mod input;
+mod item;
+mod look;
+mod monster;
+mod mouse;
+mod palette;
+mod replay;
+mod room;
+mod score;
+mod shop;
+mod stats;
+mod terminal;
+mod tiles;
+mod title;
+mod torch;
+mod travel;
+mod vault;
+mod widgets;
+
+use ai::DijkstraMap;
+use camera::{Camera, CameraMode};
+use cli::Command;
+use config::{Settings, MAX_MAP_SIZE};
+use dice::Dice;
+use dungeon::{Floors, Stairs};
+use effects::Status;
+use hidden::{HMatrix, Trap};
+use input::Action;
+use item::ITEMS;
+use monster::{Behavior, Monster, MONSTERS, ON_HIT_CHANCE};
+use palette::{Hue, Palette, PALETTES};
+use replay::{Recorder, Replay};
+use room::{RMatrix, RoomKind};
+use shop::{Shop, ShopWindowContent};
+use stats::{GameOverWindowContent, RunStats};
+use terminal::TerminalGuard;
+use tiles::{RoomTiles, Tileset, TILESETS};
+use widgets::{place, Dialog, Outcome, ProgressBar, Table};
 
 #[derive(Copy, Clone)]
 enum Dir {
@@ -32,45 +80,11 @@ impl Dir {
 static LEVEL_HEIGHT: usize = 9;
 static LEVEL_WIDTH: usize = 9;
 static EMPTY: usize = 0b00001111;
-static BLOCK_CHANCE: usize = 5;
-
-static BASE_ATTACK: usize = 2;
-static BASE_ARMOR: usize = 0;
-static BASE_EXP: usize = 0;
-static BASE_SPEED: usize = 2;
-
-static BASE_LEVEL_W: usize = 8;
-static BASE_LEVEL_H: usize = 8;
-
-static BASE: usize = 9;
-
-static Graphics: [RawImage; 26] = [
-    RawImage{gfx: "#################################################################################", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...######...######...######...##############################", rows: 9, cols: 9 },
-    RawImage{gfx: "##############################......###......###......###########################", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...######......###......###......###########################", rows: 9, cols: 9 },
-    RawImage{gfx: "##############################...######...######...######...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...######...######...######...######...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "##############################......###......###......###...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...######......###......###......###...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "###########################......###......###......##############################", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...###......###......###......##############################", rows: 9, cols: 9 },
-    RawImage{gfx: "###########################...........................###########################", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...###...........................###########################", rows: 9, cols: 9 },
-    RawImage{gfx: "###########################......###......###......######...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...###......###......###......######...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "###########################...........................###...######...######...###", rows: 9, cols: 9 },
-    RawImage{gfx: "###...######...######...###...........................###...######...######...###", rows: 9, cols: 9 },
+
+static Graphics: [RawImage; 3] = [
     RawImage{gfx: "@", rows: 1, cols: 1 },
-    RawImage{gfx: "LEVEL: ", rows: 1, cols: 7 },
-    RawImage{gfx: "------------", rows: 1, cols: 11 },
-    RawImage{gfx: "ATTACK: ", rows: 1, cols: 8 },
-    RawImage{gfx: "ARMOR: ", rows: 1, cols: 7 },
-    RawImage{gfx: "SPEED: ", rows: 1, cols: 7 },
-    RawImage{gfx: "EXP: ", rows: 1, cols: 5 },
     RawImage{gfx: r"     ______        -'      '-    /            \ |              ||,   -    -   ,|| )(__/  \__)( ||/     /\     \|(_     ^^     _) \__|IIIIII|__/   |-\IIIIII/-|    \          /     `--------`                   ", rows: 13, cols: 16 },
     RawImage{gfx: r"EXPLORATION", rows: 1, cols: 11 },
-    RawImage{gfx: "?????????????????????????????????????????????????????????????????????????????????", rows: 9, cols: 9 },
 ];
 
 
@@ -89,13 +103,36 @@ struct TerminalImage {
     pos_y: isize,
     end_x: isize,
     end_y: isize,
+    color: Option<Hue>,
 }
 
 trait RenderableContent {
     fn render(&self, game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage>;
+
+    // input for the window when it has focus
+    fn handle(&mut self, _event: &Event) -> Option<Outcome> {
+        None
+    }
+
+    // left click at a position inside the window
+    fn click(&mut self, _x: usize, _y: usize, _rows: usize) -> Option<Outcome> {
+        None
+    }
+}
+
+// z-order of windows, higher layers are drawn over lower ones and the
+// topmost modal window gets the input
+#[derive(Copy, Clone, PartialEq)]
+enum Layer {
+    Base = 0,
+    Overlay = 1,
+    Modal = 2,
 }
 
 struct TerminalWindow {
+    name: &'static str,
+    layer: Layer,
+    visible: bool,
     imgs: Vec<TerminalImage>,
     content: Box<dyn RenderableContent>,
     rows: usize,
@@ -110,12 +147,26 @@ struct TerminalScreen {
     rows: usize,
     cols: usize,
     winds: Vec<TerminalWindow>,
-    screen: io::Stdout
+    screen: io::Stdout,
+    // a window went away, what was beneath has to be drawn again
+    dirty: bool,
+    color: bool,
+    palette: &'static Palette,
+    // no windows, the log is printed as plain lines
+    linear: bool,
 }
 
 struct GameVars {
     map: AMatrix,
     visit_map: VMatrix,
+    rooms: RMatrix,
+    secrets: HMatrix,
+    traps: Vec<Trap>,
+    stairs: Stairs,
+    // the other floors of the dungeon
+    floors: Floors,
+    settings: Settings,
+    tiles: RoomTiles,
     base: usize,
     hero_pos_x: usize,
     hero_pos_y: usize,
@@ -124,11 +175,87 @@ struct GameVars {
     armor: usize,
     speed: usize,
     exp: usize,
+    hp: usize,
+    max_hp: usize,
+    gold: usize,
+    inventory: Vec<usize>,
+    // poison, haste and the like, for a number of turns
+    effects: Vec<Status>,
+    // turns the torch in hand has left
+    torch: usize,
+    shop: Option<Shop>,
+    travel_cursor: Option<(usize, usize)>,
+    // tile examined in look mode
+    look_cursor: Option<(usize, usize)>,
+    turn: usize,
+    stats: RunStats,
+    monsters: Vec<Monster>,
+    log: Vec<String>,
+    rng: StdRng,
+    replay_status: Option<String>,
+    // description of the tile under the mouse
+    hover: Option<String>,
+    camera: Camera,
+    tileset: &'static Tileset,
+}
+
+// everything needed to generate the same run again
+#[derive(Copy, Clone)]
+struct RunSetup {
+    seed: u64,
+    level: usize,
+    map_w: usize,
+    map_h: usize,
+    block_chance: usize,
+    settings: Settings,
+}
+
+impl RunSetup {
+    fn new(seed: u64, level: usize, map_size: Option<(usize, usize)>, block_chance: Option<usize>, settings: Settings) -> RunSetup {
+        let (map_w, map_h) = map_size.unwrap_or((
+            (settings.level_width + level).min(MAX_MAP_SIZE),
+            (settings.level_height + level).min(MAX_MAP_SIZE),
+        ));
+
+        RunSetup {
+            seed,
+            level,
+            map_w,
+            map_h,
+            block_chance: block_chance.unwrap_or(settings.block_chance),
+            settings,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Prompt {
+    Quit,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum GameState {
+    Title,
+    Playing,
+    Quit,
 }
 
 struct Game {
     screen: TerminalScreen,
-    vars: GameVars
+    vars: GameVars,
+    state: GameState,
+    setup: RunSetup,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    // question asked by the open modal window, if any
+    prompt: Option<Prompt>,
+    // accessibility: surroundings logged every turn
+    describe: bool,
+    described: Vec<String>,
+    // log lines already printed in linear mode
+    printed: usize,
+    // actions of a loaded run, copied into the new replay file
+    resumed: Vec<(u64, Action)>,
 }
 
 impl GameVars{
@@ -136,8 +263,360 @@ impl GameVars{
         self.map.unblock_all(self.hero_pos_y/self.base, self.hero_pos_x/self.base);
     }
 
-    fn visit_room(&mut self) {
-        self.visit_map.set(self.hero_pos_y/self.base, self.hero_pos_x/self.base, 1);
+    // marks current room as visited, returns true if it was the first visit
+    fn visit_room(&mut self) -> bool {
+        let (row, col) = self.hero_cell();
+        let first = !self.visit_map.entered(row, col);
+
+        self.visit_map.visit(row, col);
+        first
+    }
+
+    fn hero_cell(&self) -> (usize, usize) {
+        (self.hero_pos_y/self.base, self.hero_pos_x/self.base)
+    }
+
+    fn enter_room(&mut self) {
+        let first = self.visit_room();
+        let (row, col) = self.hero_cell();
+
+        match (self.rooms.get(row, col), first) {
+            (RoomKind::Treasure, true) => {
+                let gold = Dice::d6(2).plus(self.level as isize * 2).roll(&mut self.rng).total;
+                self.gold += gold;
+                self.log(format!("You open a treasure chest and find {} gold.", gold));
+                self.chest_torch();
+            },
+            (RoomKind::Treasure, false) => self.log("The treasure chest lies empty.".to_string()),
+            (RoomKind::Shrine, true) => self.bless(),
+            (RoomKind::Shrine, false) => self.log("The shrine is silent now.".to_string()),
+            (RoomKind::Trap, true) => {
+                self.log("A pressure plate clicks under your foot!".to_string());
+
+                if self.check("Speed", self.speed_now(), self.settings.trap_difficulty + self.level) {
+                    self.log("You leap away from the falling blades.".to_string());
+                } else {
+                    let dmg = Dice::d6(1).plus(self.level as isize).roll(&mut self.rng).total;
+                    self.hurt(dmg, "the blades");
+                }
+            },
+            (RoomKind::Trap, false) => self.log("The sprung trap lies harmless.".to_string()),
+            (RoomKind::Lair, true) => {
+                self.log("You stumble into a monster lair!".to_string());
+                self.spawn_lair(row, col);
+            },
+            (RoomKind::Lair, false) => {},
+            (RoomKind::Merchant, true) => {
+                self.log("A merchant waves at you from behind a cart.".to_string());
+                self.shop = Some(Shop::new());
+            },
+            (RoomKind::Merchant, false) => {
+                self.log("The merchant nods at you again.".to_string());
+                self.shop = Some(Shop::new());
+            },
+            (RoomKind::Empty, _) => {},
+        }
+    }
+
+    fn bless(&mut self) {
+        let stat = self.rng.random_range(0..4);
+        let name = match stat {
+            0 => { self.attack += 1; "attack" },
+            1 => { self.armor += 1; "armor" },
+            2 => { self.speed += 1; "speed" },
+            _ => { self.max_hp += 5; self.hp += 5; "health" },
+        };
+
+        self.log(format!("You kneel at the shrine and feel your {} grow.", name));
+    }
+
+    // talk to the merchant if there is one in the current room
+    fn trade(&mut self) {
+        let (row, col) = self.hero_cell();
+
+        if self.rooms.get(row, col) == RoomKind::Merchant {
+            self.shop = Some(Shop::new());
+        } else {
+            self.log("There is nobody here to trade with.".to_string());
+        }
+    }
+
+    fn add_item(&mut self, kind: usize) {
+        let item = &ITEMS[kind];
+
+        self.attack += item.attack;
+        self.armor += item.armor;
+        self.speed += item.speed;
+        self.inventory.push(kind);
+    }
+
+    fn remove_item(&mut self, idx: usize) -> usize {
+        let kind = self.inventory.remove(idx);
+        let item = &ITEMS[kind];
+
+        self.attack -= item.attack;
+        self.armor -= item.armor;
+        self.speed -= item.speed;
+        kind
+    }
+
+    // drinks a potion from the inventory, takes a turn; one that does
+    // something right now goes before the others
+    fn use_potion(&mut self) {
+        let potions: Vec<usize> = (0..self.inventory.len()).filter(|&i| ITEMS[self.inventory[i]].is_potion()).collect();
+        let useful = |i: &&usize| {
+            let item = &ITEMS[self.inventory[**i]];
+            (item.heal_dice().is_some() && self.hp < self.max_hp) || item.effect.is_some_and(|e| !self.has_effect(e))
+        };
+
+        let Some(&idx) = potions.iter().find(useful).or(potions.first()) else {
+            self.log("You have nothing to drink.".to_string());
+            return;
+        };
+
+        let item = &ITEMS[self.remove_item(idx)];
+
+        match item.heal_dice() {
+            Some(dice) => {
+                let roll = dice.roll(&mut self.rng);
+                let heal = roll.total.min(self.max_hp - self.hp);
+
+                self.hp += heal;
+                self.log(format!("You drink the {}: {} heals {}.", item.name, roll, heal));
+            },
+            None => self.log(format!("You drink the {}.", item.name)),
+        }
+
+        if let Some(effect) = item.effect {
+            self.afflict(effect);
+        }
+
+        self.end_turn();
+    }
+
+    fn hurt(&mut self, dmg: usize, source: &str) {
+        self.stats.damage_taken += dmg.min(self.hp);
+        self.hp = self.hp.saturating_sub(dmg);
+
+        if self.is_dead() {
+            self.log(format!("You take {} from {}. You die...", dmg, source));
+        } else {
+            self.log(format!("You take {} from {}.", dmg, source));
+        }
+    }
+
+    fn log(&mut self, msg: String) {
+        self.log.push(msg);
+    }
+
+    fn tile_at(&self, x: usize, y: usize) -> char {
+        let (row, col) = (y / self.base, x / self.base);
+
+        if row >= self.map.matrix.rows || col >= self.map.matrix.cols {
+            return '#';
+        }
+
+        self.tiles.tile(self.visible_doors(row, col), x % self.base, y % self.base)
+    }
+
+    fn is_walkable(&self, x: usize, y: usize) -> bool {
+        self.tile_at(x, y) != '#'
+    }
+
+    fn monster_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.monsters.iter().position(|m| m.pos_x == x && m.pos_y == y)
+    }
+
+    fn is_dead(&self) -> bool {
+        self.hp == 0
+    }
+
+    fn spawn_monsters(&mut self) {
+        let rows = self.map.matrix.rows;
+        let cols = self.map.matrix.cols;
+        let (hero_row, hero_col) = self.hero_cell();
+
+        for _ in 0..self.settings.monsters + self.level {
+            let row = self.rng.random_range(1..rows-1);
+            let col = self.rng.random_range(1..cols-1);
+
+            if (row, col) == (hero_row, hero_col) || self.map.get(row, col) == 0 {
+                continue;
+            }
+
+            let x = col * self.base + self.base / 2;
+            let y = row * self.base + self.base / 2;
+
+            if self.monster_at(x, y).is_some() {
+                continue;
+            }
+
+            self.spawn_monster(x, y);
+        }
+    }
+
+    fn spawn_monster(&mut self, x: usize, y: usize) {
+        let kinds: Vec<usize> = (0..MONSTERS.len()).filter(|&k| !MONSTERS[k].boss && MONSTERS[k].min_level <= self.level).collect();
+        let kind = kinds[self.rng.random_range(0..kinds.len())];
+        let hp = MONSTERS[kind].hp_dice().roll(&mut self.rng).total.max(1);
+
+        self.monsters.push(Monster::new(kind, x, y, hp));
+    }
+
+    // fills the middle of the room with monsters, keeping clear of the hero
+    fn spawn_lair(&mut self, row: usize, col: usize) {
+        let cx = col * self.base + self.base / 2;
+        let cy = row * self.base + self.base / 2;
+        let count = self.rng.random_range(1..=self.settings.lair_size);
+        let mut spawned = 0;
+
+        for (dx, dy) in [(0, 0), (-1, -1), (1, 1), (1, -1), (-1, 1), (0, -1), (0, 1), (-1, 0), (1, 0)] {
+            if spawned == count {
+                break;
+            }
+
+            let x = (cx as isize + dx) as usize;
+            let y = (cy as isize + dy) as usize;
+            let near_hero = x.abs_diff(self.hero_pos_x) + y.abs_diff(self.hero_pos_y) <= 1;
+
+            if self.is_walkable(x, y) && self.monster_at(x, y).is_none() && !near_hero {
+                self.spawn_monster(x, y);
+
+                let m = self.monsters.last_mut().unwrap();
+                m.awake = true;
+                m.home = Some((x, y));
+                m.behavior = Behavior::Guard;
+                spawned += 1;
+            }
+        }
+    }
+
+    // one hero action: walk into free tile or attack monster standing there
+    fn move_hero(&mut self, dir: Dir) {
+        let (x, y) = match dir {
+            Dir::Up => (self.hero_pos_x, self.hero_pos_y - 1),
+            Dir::Down => (self.hero_pos_x, self.hero_pos_y + 1),
+            Dir::Right => (self.hero_pos_x + 1, self.hero_pos_y),
+            Dir::Left => (self.hero_pos_x - 1, self.hero_pos_y),
+        };
+
+        if let Some(idx) = self.monster_at(x, y) {
+            self.hero_attack(idx);
+        } else if self.is_walkable(x, y) {
+            let cell = self.hero_cell();
+            self.hero_pos_x = x;
+            self.hero_pos_y = y;
+
+            if self.hero_cell() != cell {
+                self.enter_room();
+            }
+
+            self.spring_trap();
+        } else {
+            return;
+        }
+
+        self.end_turn();
+    }
+
+    fn hero_attack(&mut self, idx: usize) {
+        let dice = Dice::d6(self.settings.attack_dice).plus(self.attack_now() as isize);
+        let roll = dice.roll(&mut self.rng);
+        let name = self.monsters[idx].kind().name;
+        let armor = self.monsters[idx].kind().armor;
+
+        self.monsters[idx].awake = true;
+
+        self.log(format!("You roll {} vs armor {}", roll, armor));
+
+        if roll.total <= armor {
+            self.log(format!("The {} shrugs off your blow.", name));
+            return;
+        }
+
+        let dmg = roll.total - armor;
+        let monster = &mut self.monsters[idx];
+        self.stats.damage_dealt += dmg.min(monster.hp);
+        monster.hp = monster.hp.saturating_sub(dmg);
+
+        if monster.is_dead() {
+            self.stats.monsters_killed += 1;
+            let exp = monster.kind().exp;
+            let gold = monster.kind().gold_dice().roll(&mut self.rng).total;
+            self.monsters.remove(idx);
+            self.exp += exp;
+            self.gold += gold;
+            self.log(format!("You hit the {} for {} and kill it (+{} exp).", name, dmg, exp));
+
+            if gold > 0 {
+                self.log(format!("The {} drops {} gold.", name, gold));
+            }
+        } else {
+            self.log(format!("You hit the {} for {}.", name, dmg));
+        }
+    }
+
+    fn monsters_turn(&mut self) {
+        let dmap = DijkstraMap::new(self, self.hero_pos_x, self.hero_pos_y);
+
+        for idx in 0..self.monsters.len() {
+            if self.is_dead() {
+                break;
+            }
+
+            let m = &self.monsters[idx];
+            let dist = m.pos_x.abs_diff(self.hero_pos_x) + m.pos_y.abs_diff(self.hero_pos_y);
+
+            if dist != 1 {
+                self.monster_act(idx, &dmap);
+                continue;
+            }
+
+            let name = m.kind().name;
+
+            if !m.awake {
+                self.monsters[idx].awake = true;
+                self.log(format!("The {} wakes up.", name));
+                continue;
+            }
+
+            if self.monsters[idx].behavior == Behavior::Flee {
+                self.monster_act(idx, &dmap);
+                continue;
+            }
+
+            self.monster_attack(idx);
+        }
+    }
+
+    fn monster_attack(&mut self, idx: usize) {
+        let name = self.monsters[idx].kind().name;
+        let roll = self.monsters[idx].attack_dice().roll(&mut self.rng);
+        let armor = self.armor_now();
+
+        self.log(format!("The {} rolls {} vs armor {}", name, roll, armor));
+
+        if roll.total <= armor {
+            self.log(format!("Your armor holds against the {}.", name));
+            return;
+        }
+
+        self.hurt(roll.total - armor, &format!("the {}", name));
+
+        // a bite or a spell that lasts beyond the blow
+        match self.monsters[idx].kind().on_hit {
+            Some(effect) if !self.is_dead() && self.rng.random_range(0..ON_HIT_CHANCE) == 0 => self.afflict(effect),
+            _ => {},
+        }
+    }
+
+    // stat check: 1d6 + stat must reach the difficulty
+    fn check(&mut self, what: &str, stat: usize, difficulty: usize) -> bool {
+        let roll = Dice::d6(1).plus(stat as isize).roll(&mut self.rng);
+        let ok = roll.total >= difficulty;
+
+        self.log(format!("{} check: {} vs {} - {}", what, roll, difficulty, if ok { "success" } else { "failure" }));
+        ok
     }
 }
 
@@ -153,6 +632,21 @@ impl TerminalImage {
             pos_y: pos_y,
             end_x: pos_x as isize + raw.cols as isize,
             end_y: pos_y as isize + raw.rows as isize,
+            color: None,
+        }
+    }
+
+    // square image, size x size chars
+    fn block(gfx: &str, size: usize, pos_x: isize, pos_y: isize) -> TerminalImage {
+        TerminalImage {
+            gfx: gfx.to_string(),
+            rows: size,
+            cols: size,
+            pos_x,
+            pos_y,
+            end_x: pos_x + size as isize,
+            end_y: pos_y + size as isize,
+            color: None,
         }
     }
 
@@ -166,8 +660,14 @@ impl TerminalImage {
             pos_y,
             end_x: pos_x + len as isize,
             end_y: pos_y + 1,
+            color: None,
         }
     }
+
+    fn colored(mut self, hue: Hue) -> TerminalImage {
+        self.color = Some(hue);
+        self
+    }
 }
 
 fn create_borders(cols: usize, bg: char, border: bool) -> (Option<String>, Option<String>) {
@@ -205,6 +705,9 @@ impl TerminalWindow {
         let (hborder, vborder) = create_borders(cols, bg, border);
 
         return TerminalWindow{
+            name: "",
+            layer: Layer::Base,
+            visible: true,
             imgs: Vec::new(),
             content: content,
             rows: rows,
@@ -216,6 +719,16 @@ impl TerminalWindow {
         }
     }
     
+    fn named(&mut self, name: &'static str) -> &mut TerminalWindow {
+        self.name = name;
+        self
+    }
+
+    fn on(&mut self, layer: Layer) -> &mut TerminalWindow {
+        self.layer = layer;
+        self
+    }
+
     fn push_images(&mut self, mut imgs: Vec<TerminalImage>) {
         self.imgs.append(&mut imgs);
     }
@@ -232,7 +745,11 @@ impl TerminalScreen {
             rows: rows,
             cols: cols,
             winds: Vec::new(),
-            screen: io::stdout()
+            screen: io::stdout(),
+            dirty: false,
+            color: true,
+            palette: &PALETTES[0],
+            linear: false,
         }
     }
 
@@ -245,10 +762,71 @@ impl TerminalScreen {
         pos_y: usize,
         border: bool,
         bg: char
-    ) {
+    ) -> &mut TerminalWindow {
         self.winds.push(TerminalWindow::new(Box::new(content), rows, cols, pos_x, pos_y, border, bg));
+        self.winds.last_mut().unwrap()
     }
-    
+
+    fn window(&self, name: &str) -> Option<&TerminalWindow> {
+        self.winds.iter().find(|w| w.name == name)
+    }
+
+    fn has_window(&self, name: &str) -> bool {
+        self.window(name).is_some()
+    }
+
+    fn remove_window(&mut self, name: &str) {
+        self.winds.retain(|w| w.name != name);
+        self.dirty = true;
+    }
+
+    fn set_visible(&mut self, name: &str, visible: bool) {
+        for w in self.winds.iter_mut().filter(|w| w.name == name) {
+            w.visible = visible;
+        }
+        self.dirty = true;
+    }
+
+    // visible windows by layer, in the order they were added within one
+    fn draw_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.winds.len()).filter(|&i| self.winds[i].visible).collect();
+        order.sort_by_key(|&i| self.winds[i].layer as usize);
+        order
+    }
+
+    fn focused_idx(&self) -> Option<usize> {
+        self.draw_order().into_iter().rev().find(|&i| self.winds[i].layer == Layer::Modal)
+    }
+
+    // top of the modal stack, the one input goes to
+    fn focused(&mut self) -> Option<&mut TerminalWindow> {
+        let idx = self.focused_idx()?;
+        Some(&mut self.winds[idx])
+    }
+
+    // keys go to the focused window, clicks only when they land inside it
+    fn dispatch(&mut self, event: &Event) -> Option<Outcome> {
+        let idx = self.focused_idx()?;
+        let w = &mut self.winds[idx];
+
+        match event {
+            Event::Mouse(m) if m.kind == MouseEventKind::Down(MouseButton::Left) => {
+                let x = (m.column as usize).checked_sub(w.pos_x)?;
+                let y = (m.row as usize).checked_sub(w.pos_y)?;
+
+                if x < w.cols && y < w.rows { w.content.click(x, y, w.rows) } else { None }
+            },
+            _ => w.content.handle(event),
+        }
+    }
+
+    fn pop_modal(&mut self) {
+        if let Some(idx) = self.focused_idx() {
+            self.winds.remove(idx);
+            self.dirty = true;
+        }
+    }
+
     fn render_window(&mut self, idx: usize) {
         let wind = &mut self.winds[idx];
 
@@ -265,6 +843,11 @@ impl TerminalScreen {
         }
 
         for img in &wind.imgs {
+            let color = img.color.filter(|_| self.color).map(|h| self.palette.color(h));
+
+            if let Some(c) = color {
+                queue!(self.screen, style::SetForegroundColor(c)).unwrap();
+            }
 
             let mut trim_left: isize = 0;
             let mut trim_up: isize = 0;
@@ -303,6 +886,10 @@ impl TerminalScreen {
                     queue!(self.screen, style::Print(img_line)).unwrap();
                 }
             }
+
+            if color.is_some() {
+                queue!(self.screen, style::ResetColor).unwrap();
+            }
         }
     }
 
@@ -317,13 +904,22 @@ struct Matrix {
 
 struct AMatrix {
     matrix: Matrix,
-    rng: rand::rngs::ThreadRng,
+    block_chance: usize,
+    rng: StdRng,
 }
 
+// what the hero knows of every room
 struct VMatrix {
     matrix: Matrix,
 }
 
+static UNKNOWN: usize = 0;
+static VISITED: usize = 1;
+// lit up by the torch from nearby, not entered yet
+static SEEN: usize = 2;
+// entered once, then lost in the dark
+static FORGOTTEN: usize = 3;
+
 impl Matrix {
     fn new(rows: usize, cols: usize, fill: usize) -> Matrix {
         return Matrix{
@@ -334,11 +930,11 @@ impl Matrix {
     }
 
     fn get(&self, row: usize, col: usize) -> usize {
-        return self.data[self.rows * row + col];
+        return self.data[self.cols * row + col];
     }
 
     fn set(&mut self, row: usize, col: usize, val: usize) { 
-        self.data[self.rows * row + col] = val;
+        self.data[self.cols * row + col] = val;
     }
 }
 
@@ -348,7 +944,7 @@ impl VMatrix {
             matrix: Matrix::new(h, w, 0)
         };
 
-        v.visit(start_x, start_y);
+        v.visit(start_y, start_x);
 
         return v;
     }
@@ -362,20 +958,49 @@ impl VMatrix {
     }
 
     fn visit(&mut self, row: usize, col: usize) { 
-        self.set(row, col, 1);
+        self.set(row, col, VISITED);
     }
 
     fn forget(&mut self, row: usize, col: usize) { 
-        self.set(row, col, 0);
+        match self.get(row, col) {
+            v if v == VISITED => self.set(row, col, FORGOTTEN),
+            v if v == SEEN => self.set(row, col, UNKNOWN),
+            _ => {},
+        }
+    }
+
+    fn see(&mut self, row: usize, col: usize) {
+        match self.get(row, col) {
+            v if v == UNKNOWN => self.set(row, col, SEEN),
+            v if v == FORGOTTEN => self.set(row, col, VISITED),
+            _ => {},
+        }
+    }
+
+    // on the map
+    fn known(&self, row: usize, col: usize) -> bool {
+        let v = self.get(row, col);
+        v == VISITED || v == SEEN
+    }
+
+    // whatever the room held has been found already
+    fn entered(&self, row: usize, col: usize) -> bool {
+        let v = self.get(row, col);
+        v == VISITED || v == FORGOTTEN
+    }
+
+    fn entered_count(&self) -> usize {
+        self.matrix.data.iter().filter(|&&v| v == VISITED || v == FORGOTTEN).count()
     }
 }
 
 impl AMatrix {
-    fn new(width: usize, height: usize) -> AMatrix {
+    fn new(width: usize, height: usize, block_chance: usize, rng: StdRng) -> AMatrix {
 
         let mut map = AMatrix{
             matrix: Matrix::new(height, width, EMPTY),
-            rng: rand::rng(),
+            block_chance,
+            rng,
         };
         
         // block vertical borders
@@ -458,7 +1083,6 @@ impl AMatrix {
         }
     }
 
-    #[allow(dead_code)]
     fn random_cell(&mut self) -> (usize, usize) {
         return (
             self.rng.random_range(1..self.matrix.rows-1),
@@ -473,7 +1097,7 @@ impl AMatrix {
         // Adjust these masks based on your specific bit logic
         if v & 0b1111 == 0 { return; } 
 
-        if self.rng.random_range(0..10) < BLOCK_CHANCE {
+        if self.rng.random_range(0..10) < self.block_chance {
             let choice = self.rng.random_range(0..6);
             match choice {
                 0 => self.block(row, col, Dir::Left),
@@ -503,40 +1127,85 @@ impl RenderableContent for MapWindowContent {
         let win_w = cols;
         let mut imgs = Vec::new();
 
-        let camera_st_x = if game.hero_pos_x >= win_w / 2 { game.hero_pos_x - win_w / 2 } else { 0 };
-        let camera_end_x = game.hero_pos_x + win_w / 2;
-        let camera_st_y = if game.hero_pos_y >= win_h / 2 { game.hero_pos_y - win_h / 2 } else { 0 };
-        let camera_end_y = game.hero_pos_y + win_h / 2;
+        let (x_pad, y_pad) = game.camera.pad();
 
-        let st_cell_left = camera_st_x / game.base;
-        let end_cell_right = camera_end_x / game.base;
-        let st_cell_up = camera_st_y / game.base;
-        let end_cell_down = camera_end_y / game.base;
+        let st_cell_left = x_pad.max(0) as usize / game.base;
+        let end_cell_right = ((x_pad + win_w as isize - 1).max(0) as usize / game.base).min(game.map.matrix.cols - 1);
+        let st_cell_up = y_pad.max(0) as usize / game.base;
+        let end_cell_down = ((y_pad + win_h as isize - 1).max(0) as usize / game.base).min(game.map.matrix.rows - 1);
         
-        // get wall images (0..16) and their pos
+        // room images and their pos
         for row in st_cell_up..end_cell_down+1 {
             for col in st_cell_left..end_cell_right+1 {
-                
-                let x_pad: isize = (game.hero_pos_x as isize) - (win_w / 2) as isize;
-                let y_pad: isize = (game.hero_pos_y as isize) - (win_h / 2) as isize;
-
                 let x = ((col * game.base) as isize) - x_pad;
                 let y = ((row * game.base) as isize) - y_pad;
 
-                let idx: usize = if game.visit_map.get(row, col) == 0 {
-                    25
+                let gfx = if !game.visit_map.known(row, col) {
+                    game.tiles.unknown()
                 } else {
-                    game.map.get(row, col)
+                    game.tiles.room(game.visible_doors(row, col))
                 };
 
-                imgs.push(TerminalImage::new(idx, x, y));
+                imgs.push(TerminalImage::block(&game.tileset.draw(gfx), game.base, x, y));
+            }
+        }
+
+        // Stairs, once their room is known
+        for cell in [Some(game.stairs.down), game.stairs.up].into_iter().flatten() {
+            let (sx, sy) = game.stairs_tile(cell);
+
+            if game.visit_map.known(cell.0, cell.1) {
+                let glyph = game.stairs_at(sx, sy).unwrap();
+                imgs.push(TerminalImage::with_text(glyph.to_string(), sx as isize - x_pad, sy as isize - y_pad).colored(Hue::Info));
+            }
+        }
+
+        // Traps already found
+        for t in game.traps.iter().filter(|t| t.found) {
+            let x = t.pos_x as isize - x_pad;
+            let y = t.pos_y as isize - y_pad;
+
+            if x >= 0 && y >= 0 && x < win_w as isize && y < win_h as isize {
+                imgs.push(TerminalImage::with_text("^".to_string(), x, y).colored(Hue::Trap));
+            }
+        }
+
+        // Monsters (only in already visited rooms)
+        for m in &game.monsters {
+            if !game.visit_map.known(m.pos_y / game.base, m.pos_x / game.base) {
+                continue;
+            }
+
+            let x = m.pos_x as isize - x_pad;
+            let y = m.pos_y as isize - y_pad;
+
+            if x >= 0 && y >= 0 && x < win_w as isize && y < win_h as isize {
+                imgs.push(TerminalImage::with_text(m.kind().glyph.to_string(), x, y).colored(Hue::Monster));
             }
         }
 
         // Hero
-        let pos_x = cols as isize / 2;
-        let pos_y = rows as isize / 2;
-        imgs.push(TerminalImage::new(16, pos_x, pos_y));
+        let pos_x = game.hero_pos_x as isize - x_pad;
+        let pos_y = game.hero_pos_y as isize - y_pad;
+
+        if pos_x >= 0 && pos_y >= 0 && pos_x < win_w as isize && pos_y < win_h as isize {
+            imgs.push(TerminalImage::new(0, pos_x, pos_y).colored(Hue::Hero));
+        }
+
+        // Travel destination picked on the map
+        if let Some((row, col)) = game.travel_cursor {
+            let x = (col * game.base + game.base / 2) as isize - x_pad;
+            let y = (row * game.base + game.base / 2) as isize - y_pad;
+            imgs.push(TerminalImage::with_text("X".to_string(), x, y).colored(Hue::Cursor));
+        }
+
+        // Look cursor, brackets keep the examined glyph visible
+        if let Some((x, y)) = game.look_cursor {
+            let x = x as isize - x_pad;
+            let y = y as isize - y_pad;
+            imgs.push(TerminalImage::with_text("[".to_string(), x - 1, y).colored(Hue::Cursor));
+            imgs.push(TerminalImage::with_text("]".to_string(), x + 1, y).colored(Hue::Cursor));
+        }
 
         imgs 
     }
@@ -545,23 +1214,36 @@ impl RenderableContent for MapWindowContent {
 struct StatWindowContent;
 
 impl RenderableContent for StatWindowContent {
-    fn render(&self, game: &GameVars, _rows: usize, _cols: usize) -> Vec<TerminalImage> {
+    fn render(&self, game: &GameVars, _rows: usize, cols: usize) -> Vec<TerminalImage> {
         let mut imgs = Vec::new();
 
-        // Labels
-        imgs.push(TerminalImage::new(17, 1, 1)); // LEVEL:
-        imgs.push(TerminalImage::new(18, 1, 2)); // 
-        imgs.push(TerminalImage::new(19, 1, 3)); // ATTACK:
-        imgs.push(TerminalImage::new(20, 1, 4)); // ARMOR:
-        imgs.push(TerminalImage::new(21, 1, 5)); // SPEED:
-        imgs.push(TerminalImage::new(22, 1, 6)); // EXP:
-
-        // Values
-        imgs.push(TerminalImage::with_text(game.level.to_string(), 10, 1));
-        imgs.push(TerminalImage::with_text(game.attack.to_string(), 10, 3));
-        imgs.push(TerminalImage::with_text(game.armor.to_string(), 10, 4));
-        imgs.push(TerminalImage::with_text(game.speed.to_string(), 10, 5));
-        imgs.push(TerminalImage::with_text(game.exp.to_string(), 10, 6));
+        let level = Table::new(vec![("LEVEL:", game.level.to_string())]);
+        imgs.extend(place(level.render(game, 1, cols - 1), 1, 1));
+        imgs.push(TerminalImage::with_text("-".repeat(cols - 4), 1, 2));
+
+        let stats = Table::new(vec![
+            ("ATTACK:", game.attack_now().to_string()),
+            ("ARMOR:", game.armor_now().to_string()),
+            ("SPEED:", game.speed_now().to_string()),
+            ("EXP:", game.exp.to_string()),
+            ("HP:", format!("{}/{}", game.hp, game.max_hp)),
+            ("GOLD:", game.gold.to_string()),
+        ]);
+        imgs.extend(place(stats.render(game, 6, cols - 1), 1, 3));
+
+        let mut x = 1;
+        for status in &game.effects {
+            imgs.push(TerminalImage::with_text(status.effect.badge().to_string(), x, 9).colored(status.effect.hue()));
+            x += 3;
+        }
+
+        let hp = ProgressBar::new("HP", game.hp, game.max_hp);
+        imgs.extend(place(hp.render(game, 1, cols - 2), 1, 10));
+
+        if game.torch_on() {
+            let torch = ProgressBar::new("TORCH", game.torch, game.settings.torch);
+            imgs.extend(place(torch.render(game, 1, cols - 2), 1, 11));
+        }
 
         imgs 
     }
@@ -571,7 +1253,7 @@ struct SkullWindowContent;
 impl RenderableContent for SkullWindowContent {
     fn render(&self, _game: &GameVars, _rows: usize, _cols: usize) -> Vec<TerminalImage> {
         let mut imgs = Vec::new();
-        imgs.push(TerminalImage::new(23, 2, 0));
+        imgs.push(TerminalImage::new(1, 2, 0));
         imgs 
     }
 }
@@ -579,71 +1261,132 @@ impl RenderableContent for SkullWindowContent {
 struct BannerWindowContent;
 
 impl RenderableContent for BannerWindowContent {
-    fn render(&self, _game: &GameVars, _rows: usize, _cols: usize) -> Vec<TerminalImage> {
+    fn render(&self, game: &GameVars, _rows: usize, _cols: usize) -> Vec<TerminalImage> {
         let mut imgs = Vec::new();
-        imgs.push(TerminalImage::new(24, 32, 0));
+
+        match &game.replay_status {
+            Some(status) => imgs.push(TerminalImage::with_text(status.clone(), 1, 0)),
+            None => imgs.push(TerminalImage::new(2, 32, 0)),
+        }
+
         imgs 
     }
 }
 
 struct LogWindowContent;
 impl RenderableContent for LogWindowContent {
-    fn render(&self, _game: &GameVars, _rows: usize, _cols: usize) -> Vec<TerminalImage> {
-        Vec::new() // Empty for now
+    fn render(&self, game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let mut imgs = Vec::new();
+        let shown = if game.hover.is_some() { rows - 1 } else { rows };
+        let st = game.log.len().saturating_sub(shown);
+
+        // newest message at the bottom
+        for (y, msg) in game.log[st..].iter().enumerate() {
+            let line: String = msg.chars().take(cols).collect();
+            imgs.push(TerminalImage::with_text(line, 0, y as isize));
+        }
+
+        if let Some(hover) = game.hover.as_ref() {
+            let line: String = hover.chars().take(cols).collect();
+            imgs.push(TerminalImage::with_text(line, 0, shown as isize).colored(Hue::Info));
+        }
+
+        imgs
     }
 }
 
 impl Game {
-    fn new(screen_w: usize, screen_h: usize, base: usize) -> Game {
-        let level: usize = 1;
-        let base: usize = base;
+    // the whole run follows from the setup and the actions taken
+    fn new(screen_w: usize, screen_h: usize, setup: &RunSetup) -> Game {
+        let level: usize = setup.level;
+        let settings = setup.settings;
+        let base: usize = settings.base;
+        let seed = setup.seed;
         
-        let w = BASE_LEVEL_W + level;
-        let h = BASE_LEVEL_H + level;
+        let w = setup.map_w;
+        let h = setup.map_h;
 
         let hero_pos_x: usize = w/2 * base + base/2;
         let hero_pos_y: usize = h/2 * base + base/2;
         
-        let map: AMatrix = AMatrix::new(w, h);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let map: AMatrix = AMatrix::new(w, h, setup.block_chance, StdRng::from_rng(&mut rng));
         let visit_map: VMatrix = VMatrix::new(w, h, w/2, h/2);
+        let rooms: RMatrix = RMatrix::new(w, h, h/2, w/2, &mut rng);
+        let secrets: HMatrix = HMatrix::new(w, h);
 
         let mut game: Game =  Game {
             screen: TerminalScreen::new(screen_w, screen_h),
             vars: GameVars {
                 map: map,
                 visit_map: visit_map,
+                rooms,
+                secrets,
+                traps: Vec::new(),
+                stairs: Stairs { down: (h/2, w/2), up: None },
+                floors: Floors::new(),
+                settings,
+                tiles: RoomTiles::new(base, settings.door_width),
                 base: base,
                 hero_pos_x: hero_pos_x,
                 hero_pos_y: hero_pos_y,
                 level: level,
-                attack: BASE_ATTACK,
-                armor: BASE_ARMOR,
-                speed: BASE_SPEED,
-                exp: BASE_EXP
-            }
+                attack: settings.attack,
+                armor: settings.armor,
+                speed: settings.speed,
+                exp: settings.exp,
+                hp: settings.hp,
+                max_hp: settings.hp,
+                gold: settings.gold,
+                inventory: Vec::new(),
+                effects: Vec::new(),
+                torch: settings.torch,
+                shop: None,
+                travel_cursor: None,
+                look_cursor: None,
+                turn: 0,
+                stats: RunStats::new(level),
+                monsters: Vec::new(),
+                log: Vec::new(),
+                rng,
+                replay_status: None,
+                hover: None,
+                camera: Camera::new(CameraMode::Center),
+                tileset: &TILESETS[0],
+            },
+            state: GameState::Title,
+            setup: *setup,
+            recorder: None,
+            replay: None,
+            prompt: None,
+            describe: false,
+            described: Vec::new(),
+            printed: 0,
+            resumed: Vec::new(),
         };
-        game.vars.set_st_hero_pos();
+        game.vars.fill_floor();
+        game.vars.light_rooms();
 
         return game;
     }
     
-    fn prepare_pysical_terminal(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        execute!(&self.screen.screen, EnterAlternateScreen)?;
-        execute!(&self.screen.screen, Clear(ClearType::All))?;
-        execute!(&self.screen.screen, cursor::Hide)?;
-        enable_raw_mode()?;
-        Ok(())
-    }
+    fn render(&mut self) {
+        if self.screen.linear {
+            self.print_log();
+            return;
+        }
 
-    fn leave_pysical_terminal(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        execute!(&self.screen.screen, LeaveAlternateScreen)?;
-        disable_raw_mode()?; 
-        execute!(&self.screen.screen, cursor::Show)?;
-        Ok(())
-    }
+        if self.screen.dirty {
+            queue!(self.screen.screen, Clear(ClearType::All)).unwrap();
+            self.screen.dirty = false;
+        }
 
-    fn render(&mut self) {
-        for w in 0..self.screen.winds.len() {
+        if let Some(map) = self.screen.window("map") {
+            let (cols, rows) = (map.cols, map.rows);
+            self.vars.update_camera(cols, rows);
+        }
+
+        for w in self.screen.draw_order() {
             {
                 let wind = &mut self.screen.winds[w];
                 wind.clear();
@@ -654,47 +1397,338 @@ impl Game {
         }
     }
 
+    // shop window sits on top of the map while the merchant is open
+    fn update_shop_window(&mut self) {
+        let open = self.screen.has_window("shop");
+
+        if self.vars.shop.is_some() && !open {
+            self.screen.add_window(ShopWindowContent, 11, 28, 25, 7, true, ' ').named("shop").on(Layer::Overlay);
+        } else if self.vars.shop.is_none() && open {
+            self.screen.remove_window("shop");
+        }
+    }
+
+    // runs one action in whatever is currently open, false ends the run
+    fn apply(&mut self, action: Action) -> bool {
+        let vars = &mut self.vars;
+        vars.hover = None;
+
+        if vars.shop.is_some() {
+            match action {
+                Action::Up => vars.shop_select(-1),
+                Action::Down => vars.shop_select(1),
+                Action::Switch => vars.shop_switch(),
+                Action::Confirm => vars.shop_confirm(),
+                Action::Cancel | Action::Quit => vars.shop = None,
+                _ => {},
+            }
+            return true;
+        }
+
+        if vars.look_cursor.is_some() {
+            match action {
+                Action::Up => vars.move_look_cursor(Dir::Up),
+                Action::Down => vars.move_look_cursor(Dir::Down),
+                Action::Right => vars.move_look_cursor(Dir::Right),
+                Action::Left => vars.move_look_cursor(Dir::Left),
+                Action::Switch => vars.look_next(),
+                Action::Confirm | Action::Cancel | Action::Quit | Action::Look => vars.look_cursor = None,
+                _ => {},
+            }
+            return true;
+        }
+
+        if vars.travel_cursor.is_some() {
+            match action {
+                Action::Up => vars.move_travel_cursor(Dir::Up),
+                Action::Down => vars.move_travel_cursor(Dir::Down),
+                Action::Right => vars.move_travel_cursor(Dir::Right),
+                Action::Left => vars.move_travel_cursor(Dir::Left),
+                Action::Confirm => vars.confirm_travel(),
+                Action::Cancel | Action::Quit => vars.travel_cursor = None,
+                _ => {},
+            }
+            return true;
+        }
+
+        match action {
+            Action::Up => vars.move_hero(Dir::Up),
+            Action::Down => vars.move_hero(Dir::Down),
+            Action::Right => vars.move_hero(Dir::Right),
+            Action::Left => vars.move_hero(Dir::Left),
+            Action::Trade => vars.trade(),
+            Action::Drink => vars.use_potion(),
+            Action::Search => vars.search(),
+            Action::Explore => vars.auto_explore(),
+            Action::Travel => vars.start_travel_select(),
+            Action::Look => vars.start_look(),
+            Action::Descend => vars.descend(),
+            Action::Ascend => vars.ascend(),
+            Action::Camera => {
+                vars.camera.cycle();
+                vars.log(format!("Camera: {}.", vars.camera.mode.name()));
+            },
+            Action::TravelTo(x, y) => vars.travel_to(x, y),
+            Action::Cancel | Action::Quit => return false,
+            Action::Confirm | Action::Switch | Action::Help => {},
+        }
+
+        true
+    }
+
+    // opens a modal window asking the player something
+    fn ask<C: RenderableContent + 'static>(&mut self, prompt: Prompt, content: C, rows: usize, cols: usize) {
+        self.screen.add_window(content, rows, cols, 20, 10, true, ' ').on(Layer::Modal);
+        self.prompt = Some(prompt);
+    }
+
+    // input for the focused modal window, turned into actions once answered
+    fn route(&mut self, event: &Event) -> Vec<Action> {
+        let Some(outcome) = self.screen.dispatch(event) else { return Vec::new() };
+
+        self.screen.pop_modal();
+
+        match (self.prompt.take(), outcome) {
+            (Some(Prompt::Quit), Outcome::Yes) => vec![Action::Quit],
+            _ => Vec::new(),
+        }
+    }
+
+    // quitting a run from the map asks first; the answer is not recorded,
+    // only the quit it leads to. Help is not part of the run either
+    fn confirm(&mut self, actions: Vec<Action>) -> Vec<Action> {
+        let vars = &self.vars;
+        let on_map = vars.shop.is_none() && vars.travel_cursor.is_none() && vars.look_cursor.is_none();
+
+        match actions.as_slice() {
+            [Action::Quit | Action::Cancel] if on_map && !self.screen.linear => {
+                self.ask(Prompt::Quit, Dialog::new("Abandon this run?"), 3, 30);
+                Vec::new()
+            },
+            [Action::Help] => {
+                self.open_help(0);
+                Vec::new()
+            },
+            _ => actions,
+        }
+    }
+
+    fn update_replay_status(&mut self) {
+        self.vars.replay_status = self.replay.as_ref().map(|r| r.status());
+    }
+
+    // run summary over the whole layout, saved before waiting for the last key
+    fn game_over(&mut self) {
+        if self.replay.is_some() {
+            self.vars.log("End of replay.".to_string());
+        } else if let Err(e) = self.vars.save_run() {
+            self.vars.log(format!("Could not save the run: {}", e));
+        }
+
+        if let Some(recorder) = self.recorder.as_ref() {
+            self.vars.log(format!("Replay saved to {}", recorder.path.display()));
+        }
+
+        self.vars.shop = None;
+        self.vars.travel_cursor = None;
+        self.vars.look_cursor = None;
+        self.update_shop_window();
+
+        if self.screen.linear {
+            let title = if self.vars.is_dead() { "You died." } else { "Run abandoned." };
+            self.vars.log(format!("{} Score: {}.", title, self.vars.score()));
+
+            for line in self.vars.stats_lines() {
+                self.vars.log(line);
+            }
+        }
+
+        self.screen.add_window(GameOverWindowContent, 15, 69, 2, 6, true, ' ').named("game-over").on(Layer::Overlay);
+        self.render();
+        self.flush_screen();
+        input::wait_press();
+    }
+
     fn flush_screen(&mut self) {
         self.screen.screen.flush();
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>>{
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let opts = match cli::parse(&args) {
+        Ok(Command::Run(opts)) => opts,
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        },
+        Ok(Command::Version) => {
+            println!("dsix {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        },
+        Err(e) => {
+            eprintln!("dsix: {}", e);
+            eprintln!("Try 'dsix --help' for more information.");
+            std::process::exit(2);
+        },
+    };
+
+    // an explicit --config must exist, the default one is optional;
+    // recorded runs bring their own settings
+    let recorded_run = opts.replay.is_some() || opts.load.is_some();
+    let config_file = opts.config.clone().or_else(|| Some(config::config_path()).filter(|p| p.is_file() && !recorded_run));
+
+    let settings = match config::load(config_file.as_deref(), opts.difficulty.as_deref()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("dsix: {}", e);
+            std::process::exit(2);
+        },
+    };
+
+    let recorded = match opts.replay.as_ref().or(opts.load.as_ref()) {
+        Some(path) => match Replay::load(path) {
+            Ok(replay) => Some(replay),
+            Err(e) => {
+                eprintln!("dsix: {}", e);
+                std::process::exit(2);
+            },
+        },
+        None => None,
+    };
+
     // GAME
-    let mut game: Game = Game::new(20, 20, 9);     
-    game.prepare_pysical_terminal();
+    let setup = match recorded.as_ref() {
+        Some(r) => r.setup,
+        None => RunSetup::new(
+            opts.seed.unwrap_or_else(rand::random),
+            opts.start_level.unwrap_or(1),
+            opts.map_size,
+            opts.block_chance,
+            settings,
+        ),
+    };
+
+    let mut game: Game = Game::new(20, 20, &setup);     
+    game.screen.color = opts.color;
+    game.screen.palette = opts.palette;
+    game.screen.linear = opts.linear;
+    game.describe = opts.describe;
+    game.vars.camera = Camera::new(opts.camera);
+
+    crash::install_hook();
+    crash::update_context(&game);
+    let _terminal = TerminalGuard::enter(opts.linear)?;
 
     // UI layout
-    game.screen.add_window(SkullWindowContent, 13, 20, 2, 6, true, ' ');
-    game.screen.add_window(MapWindowContent, 13, 30, 24, 6, true, '.');
-    game.screen.add_window(StatWindowContent, 13, 15, 24+30+2, 6, true, ' ');
-    game.screen.add_window(BannerWindowContent, 1, 69, 2, 3, true, ' ');
-    game.screen.add_window(LogWindowContent, 3, 69, 2, 21, true, ' ');
-    
+    game.screen.add_window(SkullWindowContent, 13, 20, 2, 6, true, ' ').named("skull");
+    game.screen.add_window(MapWindowContent, 13, 30, 24, 6, true, '.').named("map");
+    game.screen.add_window(StatWindowContent, 13, 15, 24+30+2, 6, true, ' ').named("stats");
+    game.screen.add_window(BannerWindowContent, 1, 69, 2, 3, true, ' ').named("banner");
+    game.screen.add_window(LogWindowContent, 3, 69, 2, 21, true, ' ').named("log");
+
+    match recorded {
+        Some(replay) if opts.replay.is_some() => {
+            game.replay = Some(replay);
+            game.state = GameState::Playing;
+        },
+        // a loaded run is replayed at once, then played on
+        Some(replay) => {
+            game.state = GameState::Playing;
+            game.resume(replay);
+        },
+        None => game.title(&opts, config_file.as_deref()),
+    }
+
+    if game.state == GameState::Quit {
+        return Ok(());
+    }
+
+    if game.replay.is_none() {
+        let recorder = Recorder::new(&game.setup).and_then(|mut r| r.resume(&game.resumed).map(|_| r));
+
+        match recorder {
+            Ok(recorder) => game.recorder = Some(recorder),
+            Err(e) => game.vars.log(format!("Could not record a replay: {}", e)),
+        }
+    }
+
+    crash::update_context(&game);
+
     // update
+    game.announce();
+    game.update_replay_status();
     game.render();
+    game.flush_screen();
     
+    // a failing terminal still ends the run properly, the error comes last
+    let mut read_error = None;
+
     // game loop
     loop {
-        match read() {
-            Ok(k) => match k {
-                // TODO: after each movemnet, check if entering new cell
-                Event::Key(KeyEvent{code: KeyCode::Up, ..}) => game.vars.hero_pos_y -= 1,
-                Event::Key(KeyEvent{code: KeyCode::Down, ..}) => game.vars.hero_pos_y += 1,
-                Event::Key(KeyEvent{code: KeyCode::Right, ..}) => game.vars.hero_pos_x += 1,
-                Event::Key(KeyEvent{code: KeyCode::Left, ..}) => game.vars.hero_pos_x -= 1,
-                _ => break
+        let actions = match game.replay.as_mut() {
+            Some(replay) => match replay.next_action() {
+                Some(action) => vec![action],
+                None => break,
+            },
+            None => match read() {
+                Ok(event) if game.screen.focused().is_some() => game.route(&event),
+                Ok(Event::Mouse(event)) => game.mouse(event),
+                Ok(event) => {
+                    let actions = input::map_event(&event).into_iter().collect();
+                    game.confirm(actions)
+                },
+                Err(e) => {
+                    read_error = Some(e);
+                    break;
+                },
             },
-            Err(_) => todo!(),
         };
 
-        game.vars.visit_room();
+        // a click can stand for several actions
+        let mut quit = false;
+
+        for action in actions {
+            if let Some(recorder) = game.recorder.as_mut() && let Err(e) = recorder.record(action) {
+                game.vars.log(format!("Replay recording stopped: {}", e));
+                game.recorder = None;
+            }
+
+            let applied = game.apply(action);
+            crash::update_context(&game);
+
+            if !applied {
+                quit = true;
+                break;
+            }
+
+            if game.vars.is_dead() {
+                break;
+            }
+        }
+
+        if quit {
+            break;
+        }
+
+        game.update_shop_window();
+        game.announce();
+        game.update_replay_status();
         game.render();
         game.flush_screen();
+
+        if game.vars.is_dead() {
+            break;
+        }
     }
 
-    game.leave_pysical_terminal();
-    Ok(())
+    game.game_over();
+
+    match read_error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
 }
 
 
@@ -0,0 +1,90 @@
+use crate::dice::Dice;
+use crate::effects::Effect;
+
+pub struct MonsterKind {
+    pub name: &'static str,
+    pub glyph: char,
+    pub hp: &'static str,
+    pub attack: &'static str,
+    pub armor: usize,
+    pub exp: usize,
+    pub gold: &'static str,
+    pub min_level: usize,
+    // only ever met on a boss floor, one per floor
+    pub boss: bool,
+    // passed on to the hero by some of the hits that get through
+    pub on_hit: Option<Effect>,
+}
+
+// one hit in this many that gets through passes the effect on
+pub static ON_HIT_CHANCE: usize = 3;
+
+pub static MONSTERS: [MonsterKind; 8] = [
+    MonsterKind{ name: "rat", glyph: 'r', hp: "1d6", attack: "1d6", armor: 0, exp: 1, gold: "1d6-3", min_level: 1, boss: false, on_hit: Some(Effect::Poison) },
+    MonsterKind{ name: "goblin", glyph: 'g', hp: "2d6", attack: "1d6+1", armor: 1, exp: 2, gold: "1d6", min_level: 1, boss: false, on_hit: None },
+    MonsterKind{ name: "skeleton", glyph: 's', hp: "2d6+2", attack: "2d6", armor: 2, exp: 4, gold: "1d6+1", min_level: 2, boss: false, on_hit: None },
+    MonsterKind{ name: "orc", glyph: 'o', hp: "3d6", attack: "2d6+1", armor: 3, exp: 6, gold: "2d6", min_level: 3, boss: false, on_hit: None },
+    MonsterKind{ name: "troll", glyph: 'T', hp: "4d6+4", attack: "3d6", armor: 4, exp: 10, gold: "3d6", min_level: 5, boss: false, on_hit: None },
+    MonsterKind{ name: "ogre chief", glyph: 'O', hp: "6d6+6", attack: "3d6+2", armor: 4, exp: 25, gold: "4d6+10", min_level: 5, boss: true, on_hit: None },
+    MonsterKind{ name: "lich", glyph: 'L', hp: "7d6+8", attack: "4d6", armor: 5, exp: 40, gold: "5d6+15", min_level: 10, boss: true, on_hit: Some(Effect::Slow) },
+    MonsterKind{ name: "dragon", glyph: 'D', hp: "9d6+10", attack: "4d6+4", armor: 6, exp: 60, gold: "6d6+25", min_level: 15, boss: true, on_hit: Some(Effect::Blind) },
+];
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Behavior {
+    Wander,
+    Chase,
+    Flee,
+    Guard,
+}
+
+pub struct Monster {
+    pub kind: usize,
+    pub pos_x: usize,
+    pub pos_y: usize,
+    pub hp: usize,
+    pub max_hp: usize,
+    pub awake: bool,
+    pub behavior: Behavior,
+    // guards keep returning to this tile
+    pub home: Option<(usize, usize)>,
+    pub target: Option<(usize, usize)>,
+}
+
+impl Monster {
+    pub fn new(kind: usize, pos_x: usize, pos_y: usize, hp: usize) -> Monster {
+        Monster {
+            kind,
+            pos_x,
+            pos_y,
+            hp,
+            max_hp: hp,
+            awake: false,
+            behavior: Behavior::Wander,
+            home: None,
+            target: None,
+        }
+    }
+
+    pub fn kind(&self) -> &'static MonsterKind {
+        &MONSTERS[self.kind]
+    }
+
+    pub fn attack_dice(&self) -> Dice {
+        Dice::parse(self.kind().attack).unwrap()
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.hp == 0
+    }
+}
+
+impl MonsterKind {
+    pub fn hp_dice(&self) -> Dice {
+        Dice::parse(self.hp).unwrap()
+    }
+
+    pub fn gold_dice(&self) -> Dice {
+        Dice::parse(self.gold).unwrap()
+    }
+}
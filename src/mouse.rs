@@ -0,0 +1,134 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use crate::input::Action;
+use crate::monster::Behavior;
+use crate::room::RoomKind;
+use crate::{Game, GameVars, TerminalScreen};
+
+impl TerminalScreen {
+    // topmost visible window under a screen position and the position inside it
+    pub fn window_at(&self, col: usize, row: usize) -> Option<(&'static str, usize, usize)> {
+        self.draw_order().into_iter().rev().find_map(|idx| {
+            let w = &self.winds[idx];
+            let inside = (w.pos_x..w.pos_x + w.cols).contains(&col) && (w.pos_y..w.pos_y + w.rows).contains(&row);
+            inside.then(|| (w.name, col - w.pos_x, row - w.pos_y))
+        })
+    }
+}
+
+impl GameVars {
+    // one line about whatever the hero knows of a tile
+    pub fn describe(&self, x: usize, y: usize) -> String {
+        let (row, col) = (y / self.base, x / self.base);
+
+        if row >= self.map.matrix.rows || col >= self.map.matrix.cols || !self.visit_map.known(row, col) {
+            return "Unexplored.".to_string();
+        }
+
+        if (x, y) == (self.hero_pos_x, self.hero_pos_y) {
+            return format!("You, with {}/{} HP.", self.hp, self.max_hp);
+        }
+
+        if let Some(idx) = self.monster_at(x, y) {
+            let m = &self.monsters[idx];
+            let state = match (m.awake, m.behavior) {
+                (false, _) => "asleep",
+                (true, Behavior::Flee) => "fleeing",
+                (true, Behavior::Guard) => "guarding",
+                (true, _) => "awake",
+            };
+            return format!("A {} ({}), {}/{} HP.", m.kind().name, state, m.hp, m.max_hp);
+        }
+
+        match self.trap_at(x, y) {
+            Some(t) if self.traps[t].found => {
+                let name = self.traps[t].kind.name();
+                return format!("{} {}.", if name.starts_with('a') { "An" } else { "A" }, name);
+            },
+            _ => {},
+        }
+
+        match self.stairs_at(x, y) {
+            Some('>') => return "Stairs leading down.".to_string(),
+            Some(_) => return "Stairs leading back up.".to_string(),
+            None => {},
+        }
+
+        if !self.is_walkable(x, y) {
+            return "A stone wall.".to_string();
+        }
+
+        match self.rooms.get(row, col) {
+            RoomKind::Treasure => "Floor of a treasure room.",
+            RoomKind::Shrine => "Floor of a shrine.",
+            RoomKind::Trap => "Floor of a trapped room.",
+            RoomKind::Lair => "Floor of a monster lair.",
+            RoomKind::Merchant => "Floor of the merchant's room.",
+            RoomKind::Empty => "Stone floor.",
+        }.to_string()
+    }
+
+    // map tile under a position inside the map window
+    pub fn map_tile_at(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let (x_pad, y_pad) = self.camera.pad();
+        let tile_x = usize::try_from(x as isize + x_pad).ok()?;
+        let tile_y = usize::try_from(y as isize + y_pad).ok()?;
+
+        // the camera may show space past the last room
+        if tile_x >= self.map.matrix.cols * self.base || tile_y >= self.map.matrix.rows * self.base {
+            return None;
+        }
+
+        Some((tile_x, tile_y))
+    }
+}
+
+impl Game {
+    // hovering describes the map, clicks turn into actions so they get recorded
+    pub fn mouse(&mut self, event: MouseEvent) -> Vec<Action> {
+        let at = self.screen.window_at(event.column as usize, event.row as usize);
+
+        match event.kind {
+            MouseEventKind::Moved => {
+                self.vars.hover = match at {
+                    Some(("map", x, y)) => self.vars.map_tile_at(x, y).map(|(tx, ty)| self.vars.describe(tx, ty)),
+                    _ => None,
+                };
+                Vec::new()
+            },
+            MouseEventKind::Down(MouseButton::Left) => match at {
+                Some(("shop", _, y)) => {
+                    let rows = self.screen.window("shop").map(|w| w.rows).unwrap_or(0);
+                    self.shop_click(rows, y)
+                },
+                Some(("map", x, y)) if self.vars.travel_cursor.is_none() && self.vars.look_cursor.is_none() => {
+                    match self.vars.map_tile_at(x, y) {
+                        Some(tile) if tile != (self.vars.hero_pos_x, self.vars.hero_pos_y) => vec![Action::TravelTo(tile.0, tile.1)],
+                        _ => Vec::new(),
+                    }
+                },
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    // title switches sides, a click picks an entry and a second one trades it
+    fn shop_click(&self, rows: usize, line: usize) -> Vec<Action> {
+        let Some(shop) = self.vars.shop.as_ref() else { return Vec::new() };
+
+        if line == 0 {
+            return vec![Action::Switch];
+        }
+
+        if line == rows - 1 {
+            return vec![Action::Cancel];
+        }
+
+        match self.vars.shop_entry_at(rows, line) {
+            Some(i) if i == shop.selected => vec![Action::Confirm],
+            Some(i) if i > shop.selected => vec![Action::Down; i - shop.selected],
+            Some(i) => vec![Action::Up; shop.selected - i],
+            None => Vec::new(),
+        }
+    }
+}
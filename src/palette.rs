@@ -0,0 +1,65 @@
+use crossterm::style::Color;
+
+// what a colored image shows, the palette decides the actual color
+#[derive(Copy, Clone, PartialEq)]
+pub enum Hue {
+    Hero,
+    Monster,
+    Trap,
+    Cursor,
+    Info,
+}
+
+pub struct Palette {
+    pub name: &'static str,
+    hero: Color,
+    monster: Color,
+    trap: Color,
+    cursor: Color,
+    info: Color,
+}
+
+pub static PALETTES: [Palette; 3] = [
+    Palette {
+        name: "default",
+        hero: Color::Yellow,
+        monster: Color::Red,
+        trap: Color::Magenta,
+        cursor: Color::Cyan,
+        info: Color::Cyan,
+    },
+    // bright colors only, readable on dim or washed-out screens
+    Palette {
+        name: "high-contrast",
+        hero: Color::White,
+        monster: Color::Red,
+        trap: Color::Yellow,
+        cursor: Color::Green,
+        info: Color::White,
+    },
+    // Okabe-Ito colors, told apart with any common color blindness
+    Palette {
+        name: "colorblind",
+        hero: Color::Rgb { r: 240, g: 228, b: 66 },
+        monster: Color::Rgb { r: 230, g: 159, b: 0 },
+        trap: Color::Rgb { r: 204, g: 121, b: 167 },
+        cursor: Color::Rgb { r: 86, g: 180, b: 233 },
+        info: Color::Rgb { r: 86, g: 180, b: 233 },
+    },
+];
+
+impl Palette {
+    pub fn by_name(name: &str) -> Option<&'static Palette> {
+        PALETTES.iter().find(|p| p.name == name)
+    }
+
+    pub fn color(&self, hue: Hue) -> Color {
+        match hue {
+            Hue::Hero => self.hero,
+            Hue::Monster => self.monster,
+            Hue::Trap => self.trap,
+            Hue::Cursor => self.cursor,
+            Hue::Info => self.info,
+        }
+    }
+}
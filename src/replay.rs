@@ -0,0 +1,193 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyEventKind};
+use toml::Table;
+use crate::config::{Settings, MAX_BLOCK_CHANCE, MAX_MAP_SIZE, MAX_START_LEVEL, MIN_MAP_SIZE};
+use crate::input::Action;
+use crate::score::{create_unique, data_dir};
+use crate::RunSetup;
+
+static HEADER: &str = "dsix replay 1";
+static MAX_SPEED: f64 = 64.0;
+static MIN_SPEED: f64 = 0.25;
+
+// writes the run setup and every action with its time (ms since start)
+pub struct Recorder {
+    file: BufWriter<File>,
+    start: Instant,
+    // time already played when continuing a loaded run
+    offset: u64,
+    pub path: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(setup: &RunSetup) -> io::Result<Recorder> {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let dir = data_dir().join("replays");
+        fs::create_dir_all(&dir)?;
+
+        let (path, file) = create_unique(&dir, "replay", time)?;
+        let mut file = BufWriter::new(file);
+
+        writeln!(file, "{}", HEADER)?;
+        writeln!(file, "seed {}", setup.seed)?;
+        writeln!(file, "level {}", setup.level)?;
+        writeln!(file, "size {}x{}", setup.map_w, setup.map_h)?;
+        writeln!(file, "block {}", setup.block_chance)?;
+
+        for (key, value) in setup.settings.to_table() {
+            writeln!(file, "set {} {}", key, value)?;
+        }
+
+        Ok(Recorder { file, start: Instant::now(), offset: 0, path })
+    }
+
+    // copies actions of a loaded run so the new file holds the whole history
+    pub fn resume(&mut self, actions: &[(u64, Action)]) -> io::Result<()> {
+        for (time, action) in actions {
+            writeln!(self.file, "{} {}", time, action.name())?;
+        }
+
+        self.offset = actions.last().map(|a| a.0).unwrap_or(0);
+        self.start = Instant::now();
+        self.file.flush()
+    }
+
+    pub fn record(&mut self, action: Action) -> io::Result<()> {
+        writeln!(self.file, "{} {}", self.offset + self.start.elapsed().as_millis() as u64, action.name())?;
+        self.file.flush()
+    }
+}
+
+// newest recorded run, the one the title screen offers to continue
+pub fn latest() -> Option<PathBuf> {
+    fs::read_dir(data_dir().join("replays")).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("replay-"))
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+        .map(|entry| entry.path())
+}
+
+pub struct Replay {
+    pub setup: RunSetup,
+    pub actions: Vec<(u64, Action)>,
+    next: usize,
+    speed: f64,
+    paused: bool,
+    clock: f64,
+    last: Instant,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut lines = text.lines();
+
+        if lines.next() != Some(HEADER) {
+            return Err(format!("{}: not a dsix replay file", path.display()));
+        }
+
+        let mut seed = None;
+        let mut level = None;
+        let mut size = None;
+        let mut block = None;
+        let mut settings = Table::new();
+        let mut actions = Vec::new();
+
+        for (i, line) in lines.enumerate() {
+            let bad = || format!("{}:{}: bad replay entry '{}'", path.display(), i + 2, line);
+            let (key, value) = line.split_once(' ').ok_or_else(bad)?;
+
+            // setup lines first, then "<ms> <action>" entries
+            match key {
+                "seed" => seed = Some(value.parse::<u64>().map_err(|_| bad())?),
+                // same limits as on the command line
+                "level" => {
+                    let v = value.parse::<usize>().map_err(|_| bad())?;
+
+                    if !(1..=MAX_START_LEVEL).contains(&v) {
+                        return Err(bad());
+                    }
+                    level = Some(v);
+                },
+                "size" => {
+                    let (w, h) = value.split_once('x').ok_or_else(bad)?;
+                    let (w, h) = (w.parse::<usize>().map_err(|_| bad())?, h.parse::<usize>().map_err(|_| bad())?);
+
+                    if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&w) || !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&h) {
+                        return Err(bad());
+                    }
+                    size = Some((w, h));
+                },
+                "block" => {
+                    let v = value.parse::<usize>().map_err(|_| bad())?;
+
+                    if v > MAX_BLOCK_CHANCE {
+                        return Err(bad());
+                    }
+                    block = Some(v);
+                },
+                "set" => {
+                    let (name, v) = value.split_once(' ').ok_or_else(bad)?;
+                    settings.insert(name.to_string(), toml::Value::Integer(v.parse::<i64>().map_err(|_| bad())?));
+                },
+                _ => {
+                    let time = key.parse::<u64>().map_err(|_| bad())?;
+                    let action = Action::from_name(value).ok_or_else(bad)?;
+                    actions.push((time, action));
+                },
+            }
+        }
+
+        let seed = seed.ok_or(format!("{}: missing seed", path.display()))?;
+        let settings = Settings::from_table(settings).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let setup = RunSetup::new(seed, level.unwrap_or(1), size, block, settings);
+
+        Ok(Replay { setup, actions, next: 0, speed: 1.0, paused: false, clock: 0.0, last: Instant::now() })
+    }
+
+    pub fn status(&self) -> String {
+        let state = if self.paused { "paused" } else { "playing" };
+        format!("REPLAY {} x{} [{}/{}]  space: pause  +/-: speed  q: stop", state, self.speed, self.next, self.actions.len())
+    }
+
+    // handles playback keys, returns false if the viewer wants to stop
+    fn control(&mut self, event: Event) -> bool {
+        if let Event::Key(KeyEvent{code, kind: KeyEventKind::Press, ..}) = event {
+            match code {
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('+') => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+                KeyCode::Char('-') => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                _ => {},
+            }
+        }
+        true
+    }
+
+    // waits until the next action is due, None when done or stopped
+    pub fn next_action(&mut self) -> Option<Action> {
+        let &(due, action) = self.actions.get(self.next)?;
+
+        loop {
+            let now = Instant::now();
+            if !self.paused {
+                self.clock += now.duration_since(self.last).as_secs_f64() * 1000.0 * self.speed;
+            }
+            self.last = now;
+
+            if !self.paused && self.clock >= due as f64 {
+                self.next += 1;
+                return Some(action);
+            }
+
+            let wait = if self.paused { 100.0 } else { ((due as f64 - self.clock) / self.speed).clamp(1.0, 100.0) };
+
+            if poll(Duration::from_millis(wait as u64)).ok()? && !self.control(read().ok()?) {
+                return None;
+            }
+        }
+    }
+}
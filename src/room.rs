@@ -0,0 +1,79 @@
+use rand::Rng;
+use crate::Matrix;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum RoomKind {
+    Empty = 0,
+    Treasure = 1,
+    Shrine = 2,
+    Trap = 3,
+    Lair = 4,
+    Merchant = 5,
+}
+
+// chance (out of 100) of a room being given the kind, rest is empty
+static ROOM_CHANCES: [(RoomKind, usize); 5] = [
+    (RoomKind::Treasure, 12),
+    (RoomKind::Shrine, 6),
+    (RoomKind::Trap, 12),
+    (RoomKind::Lair, 15),
+    (RoomKind::Merchant, 4),
+];
+
+impl RoomKind {
+    fn from_usize(v: usize) -> RoomKind {
+        match v {
+            1 => RoomKind::Treasure,
+            2 => RoomKind::Shrine,
+            3 => RoomKind::Trap,
+            4 => RoomKind::Lair,
+            5 => RoomKind::Merchant,
+            _ => RoomKind::Empty,
+        }
+    }
+
+    fn random<R: Rng>(rng: &mut R) -> RoomKind {
+        let mut roll = rng.random_range(0..100);
+
+        for (kind, chance) in ROOM_CHANCES {
+            if roll < chance {
+                return kind;
+            }
+            roll -= chance;
+        }
+
+        RoomKind::Empty
+    }
+}
+
+// room kind of each cell, chosen once when the level is generated
+pub struct RMatrix {
+    matrix: Matrix,
+}
+
+impl RMatrix {
+    pub fn new<R: Rng>(w: usize, h: usize, start_row: usize, start_col: usize, rng: &mut R) -> RMatrix {
+        let mut r = RMatrix {
+            matrix: Matrix::new(h, w, RoomKind::Empty as usize)
+        };
+
+        for row in 0..h {
+            for col in 0..w {
+                r.set(row, col, RoomKind::random(rng));
+            }
+        }
+
+        // the hero always starts in a quiet room
+        r.set(start_row, start_col, RoomKind::Empty);
+
+        r
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> RoomKind {
+        RoomKind::from_usize(self.matrix.get(row, col))
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, kind: RoomKind) {
+        self.matrix.set(row, col, kind as usize);
+    }
+}
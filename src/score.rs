@@ -0,0 +1,265 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{GameVars, RenderableContent, TerminalImage};
+
+static SCORES_FILE: &str = "scores.txt";
+static MAX_SCORES: usize = 10;
+static MORGUE_LOG_LINES: usize = 20;
+
+pub struct HighScore {
+    pub score: usize,
+    pub level: usize,
+    pub exp: usize,
+    pub turns: usize,
+    pub time: u64,
+    pub end: String,
+}
+
+impl HighScore {
+    fn from_line(line: &str) -> Option<HighScore> {
+        let mut parts = line.split('\t');
+
+        Some(HighScore {
+            score: parts.next()?.parse().ok()?,
+            level: parts.next()?.parse().ok()?,
+            exp: parts.next()?.parse().ok()?,
+            turns: parts.next()?.parse().ok()?,
+            time: parts.next()?.parse().ok()?,
+            end: parts.next()?.to_string(),
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}\t{}", self.score, self.level, self.exp, self.turns, self.time, self.end)
+    }
+}
+
+// $XDG_DATA_HOME/dsix, ~/.local/share/dsix or %APPDATA%\dsix
+pub fn data_dir() -> PathBuf {
+    let base = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    base.join("dsix")
+}
+
+fn parse_scores(text: &str) -> Vec<HighScore> {
+    text.lines().filter_map(HighScore::from_line).collect()
+}
+
+pub fn load_scores() -> Vec<HighScore> {
+    let Ok(mut file) = File::open(data_dir().join(SCORES_FILE)) else { return Vec::new() };
+    let mut text = String::new();
+
+    if file.lock_shared().is_err() || file.read_to_string(&mut text).is_err() {
+        return Vec::new();
+    }
+
+    parse_scores(&text)
+}
+
+// merges the score into the table, the file stays locked for the whole
+// read-modify-write so runs finishing at the same time don't lose entries
+fn add_score(entry: HighScore) -> io::Result<usize> {
+    fs::create_dir_all(data_dir())?;
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(data_dir().join(SCORES_FILE))?;
+    file.lock()?;
+
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+
+    let mut scores = parse_scores(&text);
+    scores.sort_by(|a, b| b.score.cmp(&a.score).then(a.time.cmp(&b.time)));
+
+    // below every entry with the same score, those were there first
+    let idx = scores.iter().position(|s| s.score < entry.score).unwrap_or(scores.len());
+    scores.insert(idx, entry);
+    scores.truncate(MAX_SCORES);
+
+    let out: Vec<String> = scores.iter().map(|s| s.to_line()).collect();
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all((out.join("\n") + "\n").as_bytes())?;
+
+    // 1-based place on the table, 0 if it did not make it
+    Ok(if idx < MAX_SCORES { idx + 1 } else { 0 })
+}
+
+// <name>-<time>.txt in dir, numbered when another run used the same second
+pub fn create_unique(dir: &Path, name: &str, time: u64) -> io::Result<(PathBuf, File)> {
+    let mut n = 1;
+
+    loop {
+        let file_name = if n == 1 { format!("{}-{}.txt", name, time) } else { format!("{}-{}-{}.txt", name, time, n) };
+        let path = dir.join(file_name);
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl GameVars {
+    // depth and experience only, waiting around earns nothing
+    pub fn score(&self) -> usize {
+        self.stats.deepest_level * 500 + self.exp * 10
+    }
+
+    fn end_reason(&self) -> &'static str {
+        if self.is_dead() { "died" } else { "quit" }
+    }
+
+    // revealed part of the level, as the hero remembers it
+    pub fn map_lines(&self) -> Vec<String> {
+        let visited: Vec<(usize, usize)> = (0..self.map.matrix.rows)
+            .flat_map(|row| (0..self.map.matrix.cols).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.visit_map.known(row, col))
+            .collect();
+
+        // only the bounding box of visited rooms
+        let st_row = visited.iter().map(|c| c.0).min().unwrap_or(0);
+        let end_row = visited.iter().map(|c| c.0).max().unwrap_or(0);
+        let st_col = visited.iter().map(|c| c.1).min().unwrap_or(0);
+        let end_col = visited.iter().map(|c| c.1).max().unwrap_or(0);
+
+        (st_row * self.base..(end_row + 1) * self.base).map(|y| {
+            (st_col * self.base..(end_col + 1) * self.base).map(|x| {
+                if !self.visit_map.known(y / self.base, x / self.base) {
+                    ' '
+                } else if (x, y) == (self.hero_pos_x, self.hero_pos_y) {
+                    '@'
+                } else if let Some(idx) = self.monster_at(x, y) {
+                    self.monsters[idx].kind().glyph
+                } else if self.trap_at(x, y).is_some_and(|t| self.traps[t].found) {
+                    '^'
+                } else {
+                    self.tile_at(x, y)
+                }
+            }).collect::<String>().trim_end().to_string()
+        }).collect()
+    }
+
+    fn write_morgue(&self, time: u64) -> io::Result<PathBuf> {
+        let (path, mut file) = create_unique(&data_dir(), "morgue", time)?;
+
+        writeln!(file, "dsix morgue file")?;
+        writeln!(file)?;
+        writeln!(file, "The hero {} on level {} with a score of {}.", self.end_reason(), self.level, self.score())?;
+        writeln!(file)?;
+        writeln!(file, "Attack: {}  Armor: {}  Speed: {}  HP: {}/{}", self.attack, self.armor, self.speed, self.hp, self.max_hp)?;
+
+        if !self.effects.is_empty() {
+            let effects: Vec<String> = self.effects.iter().map(|s| format!("{} ({} turns)", s.effect.name(), s.turns)).collect();
+            writeln!(file, "Effects: {}", effects.join(", "))?;
+        }
+
+        for line in self.stats_lines() {
+            writeln!(file, "{}", line)?;
+        }
+
+        writeln!(file)?;
+        writeln!(file, "Map:")?;
+
+        for line in self.map_lines() {
+            writeln!(file, "{}", line)?;
+        }
+
+        writeln!(file)?;
+        writeln!(file, "Last messages:")?;
+
+        let st = self.log.len().saturating_sub(MORGUE_LOG_LINES);
+        for msg in &self.log[st..] {
+            writeln!(file, "{}", msg)?;
+        }
+
+        Ok(path)
+    }
+
+    // stores the run on the high-score table and dumps a morgue file
+    pub fn save_run(&mut self) -> io::Result<()> {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+        let place = add_score(HighScore {
+            score: self.score(),
+            level: self.stats.deepest_level,
+            exp: self.exp,
+            turns: self.turn,
+            time,
+            end: self.end_reason().to_string(),
+        })?;
+
+        if place > 0 {
+            self.log(format!("Your score of {} is #{} on the high-score table!", self.score(), place));
+        }
+
+        let path = self.write_morgue(time)?;
+        self.log(format!("Morgue file written to {}", path.display()));
+
+        Ok(())
+    }
+}
+
+// the table as it was when the window opened
+pub struct HighScoresWindowContent {
+    scores: Vec<HighScore>,
+}
+
+impl HighScoresWindowContent {
+    pub fn new() -> HighScoresWindowContent {
+        HighScoresWindowContent { scores: load_scores() }
+    }
+}
+
+impl RenderableContent for HighScoresWindowContent {
+    fn render(&self, _game: &GameVars, rows: usize, _cols: usize) -> Vec<TerminalImage> {
+        let mut imgs = Vec::new();
+        let scores = &self.scores;
+
+        imgs.push(TerminalImage::with_text("HIGH SCORES".to_string(), 2, 1));
+        imgs.push(TerminalImage::with_text(format!("{:>3}  {:>7}  {:>5}  {:>5}  {:>6}  {}", "#", "SCORE", "LEVEL", "EXP", "TURNS", "END"), 2, 3));
+
+        if scores.is_empty() {
+            imgs.push(TerminalImage::with_text("No runs recorded yet.".to_string(), 2, 5));
+        }
+
+        for (i, s) in scores.iter().enumerate() {
+            let line = format!("{:>3}  {:>7}  {:>5}  {:>5}  {:>6}  {}", i + 1, s.score, s.level, s.exp, s.turns, s.end);
+            imgs.push(TerminalImage::with_text(line, 2, 4 + i as isize));
+        }
+
+        imgs.push(TerminalImage::with_text("press any key".to_string(), 2, rows as isize - 1));
+
+        imgs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Settings;
+    use crate::{Game, RunSetup};
+
+    #[test]
+    fn score_counts_depth_and_experience() {
+        let mut vars = Game::new(0, 0, &RunSetup::new(1, 1, None, None, Settings::default())).vars;
+        vars.stats.deepest_level = 3;
+        vars.exp = 12;
+        assert_eq!(vars.score(), 3 * 500 + 12 * 10);
+
+        // turns spent waiting or searching add nothing
+        vars.turn = 5000;
+        assert_eq!(vars.score(), 1620);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::item::ITEMS;
+use crate::{GameVars, RenderableContent, TerminalImage};
+
+// state of an open merchant window
+pub struct Shop {
+    pub selected: usize,
+    pub selling: bool,
+}
+
+impl Shop {
+    pub fn new() -> Shop {
+        Shop { selected: 0, selling: false }
+    }
+}
+
+impl GameVars {
+    // item kinds the merchant offers on this level
+    pub fn shop_stock(&self) -> Vec<usize> {
+        (0..ITEMS.len()).filter(|&i| ITEMS[i].min_level <= self.level && (self.torch_on() || !ITEMS[i].torch)).collect()
+    }
+
+    pub fn shop_entries(&self, selling: bool) -> Vec<usize> {
+        if selling { self.inventory.clone() } else { self.shop_stock() }
+    }
+
+    // first entry shown, so the selected one stays in view
+    fn shop_first_visible(&self, rows: usize) -> usize {
+        let selected = self.shop.as_ref().map(|s| s.selected).unwrap_or(0);
+        selected.saturating_sub(rows - 5)
+    }
+
+    pub fn shop_entry_at(&self, rows: usize, line: usize) -> Option<usize> {
+        let selling = self.shop.as_ref()?.selling;
+        let i = self.shop_first_visible(rows) + line.checked_sub(3)?;
+
+        (line < rows - 1 && i < self.shop_entries(selling).len()).then_some(i)
+    }
+
+    pub fn shop_select(&mut self, delta: isize) {
+        let Some(selling) = self.shop.as_ref().map(|s| s.selling) else { return };
+        let len = self.shop_entries(selling).len();
+        let shop = self.shop.as_mut().unwrap();
+
+        if len == 0 {
+            shop.selected = 0;
+        } else {
+            shop.selected = (shop.selected as isize + delta).rem_euclid(len as isize) as usize;
+        }
+    }
+
+    pub fn shop_switch(&mut self) {
+        if let Some(shop) = self.shop.as_mut() {
+            shop.selling = !shop.selling;
+            shop.selected = 0;
+        }
+    }
+
+    pub fn shop_confirm(&mut self) {
+        let Some((selling, selected)) = self.shop.as_ref().map(|s| (s.selling, s.selected)) else { return };
+
+        if selling {
+            if selected < self.inventory.len() {
+                self.sell(selected);
+            }
+            self.shop_select(0);
+        } else if let Some(&kind) = self.shop_stock().get(selected) {
+            self.buy(kind);
+        }
+    }
+
+    fn buy(&mut self, kind: usize) {
+        let item = &ITEMS[kind];
+
+        if self.gold < item.price {
+            self.log(format!("You can't afford the {} ({} gold).", item.name, item.price));
+            return;
+        }
+
+        self.gold -= item.price;
+        self.add_item(kind);
+        self.log(format!("You buy the {} for {} gold.", item.name, item.price));
+    }
+
+    fn sell(&mut self, idx: usize) {
+        let kind = self.remove_item(idx);
+        let item = &ITEMS[kind];
+
+        self.gold += item.sell_price();
+        self.log(format!("You sell the {} for {} gold.", item.name, item.sell_price()));
+    }
+}
+
+pub struct ShopWindowContent;
+
+impl RenderableContent for ShopWindowContent {
+    fn render(&self, game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let mut imgs = Vec::new();
+        let Some(shop) = game.shop.as_ref() else { return imgs };
+
+        let title = if shop.selling { "SELL  (tab: buy)" } else { "BUY  (tab: sell)" };
+        imgs.push(TerminalImage::with_text(title.to_string(), 1, 0));
+        imgs.push(TerminalImage::with_text(format!("GOLD: {}", game.gold), 1, 1));
+
+        let entries = game.shop_entries(shop.selling);
+
+        if entries.is_empty() {
+            imgs.push(TerminalImage::with_text("nothing to sell".to_string(), 1, 3));
+        }
+
+        let visible = rows - 4;
+        let st = game.shop_first_visible(rows);
+
+        for (i, &kind) in entries.iter().enumerate().skip(st).take(visible) {
+            let item = &ITEMS[kind];
+            let price = if shop.selling { item.sell_price() } else { item.price };
+            let marker = if i == shop.selected { '>' } else { ' ' };
+            let line = format!("{}{:<w$}{:>4}", marker, item.name, price, w = cols - 7);
+
+            imgs.push(TerminalImage::with_text(line, 1, (3 + i - st) as isize));
+        }
+
+        imgs.push(TerminalImage::with_text("enter: trade  esc: leave".to_string(), 1, rows as isize - 1));
+
+        imgs
+    }
+}
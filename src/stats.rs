@@ -0,0 +1,92 @@
+use crate::{GameVars, RenderableContent, TerminalImage};
+
+pub struct RunStats {
+    pub monsters_killed: usize,
+    pub damage_dealt: usize,
+    pub damage_taken: usize,
+    pub deepest_level: usize,
+    // index 0 is level 1
+    pub turns_per_level: Vec<usize>,
+}
+
+impl RunStats {
+    // a run can start deeper than level 1
+    pub fn new(level: usize) -> RunStats {
+        RunStats {
+            monsters_killed: 0,
+            damage_dealt: 0,
+            damage_taken: 0,
+            deepest_level: level,
+            turns_per_level: vec![0; level],
+        }
+    }
+}
+
+impl GameVars {
+    // closes a hero action: the clock ticks and the monsters move
+    pub fn end_turn(&mut self) {
+        self.turn += 1;
+
+        if self.stats.turns_per_level.len() < self.level {
+            self.stats.turns_per_level.resize(self.level, 0);
+        }
+        self.stats.turns_per_level[self.level - 1] += 1;
+        self.stats.deepest_level = self.stats.deepest_level.max(self.level);
+
+        for _ in 0..self.monster_moves() {
+            self.monsters_turn();
+        }
+
+        if !self.is_dead() {
+            self.tick_effects();
+            self.burn_torch();
+        }
+    }
+
+    // on every floor seen so far
+    pub fn rooms_explored(&self) -> usize {
+        let here = self.visit_map.entered_count();
+        here + self.floors.values().map(|f| f.rooms_explored()).sum::<usize>()
+    }
+
+    pub fn stats_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Turns:           {}", self.turn),
+            format!("Level reached:   {}", self.level),
+            format!("Deepest level:   {}", self.stats.deepest_level),
+            format!("Experience:      {}", self.exp),
+            format!("Gold:            {}", self.gold),
+            format!("Rooms explored:  {}", self.rooms_explored()),
+            format!("Monsters killed: {}", self.stats.monsters_killed),
+            format!("Damage dealt:    {}", self.stats.damage_dealt),
+            format!("Damage taken:    {}", self.stats.damage_taken),
+        ];
+
+        let per_level: Vec<String> = self.stats.turns_per_level.iter()
+            .enumerate()
+            .map(|(i, t)| format!("L{}:{}", i + 1, t))
+            .collect();
+        lines.push(format!("Turns per level: {}", per_level.join(" ")));
+
+        lines
+    }
+}
+
+pub struct GameOverWindowContent;
+
+impl RenderableContent for GameOverWindowContent {
+    fn render(&self, game: &GameVars, rows: usize, _cols: usize) -> Vec<TerminalImage> {
+        let mut imgs = Vec::new();
+        let title = if game.is_dead() { "YOU DIED" } else { "RUN ABANDONED" };
+
+        imgs.push(TerminalImage::with_text(format!("{}    SCORE: {}", title, game.score()), 2, 1));
+
+        for (i, line) in game.stats_lines().into_iter().enumerate() {
+            imgs.push(TerminalImage::with_text(line, 2, 3 + i as isize));
+        }
+
+        imgs.push(TerminalImage::with_text("press any key".to_string(), 2, rows as isize - 2));
+
+        imgs
+    }
+}
@@ -0,0 +1,31 @@
+use std::io::{self, stdout};
+use crossterm::{cursor, execute};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+
+// keeps the terminal in game mode while alive; dropping it, also while
+// unwinding from a panic, gives the shell its terminal back
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    // linear mode keeps the normal screen and only reads keys
+    pub fn enter(linear: bool) -> io::Result<TerminalGuard> {
+        if !linear {
+            execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All), cursor::Hide, EnableMouseCapture)?;
+        }
+        enable_raw_mode()?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// safe to call more than once, every step is tried even if one fails
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), DisableMouseCapture, LeaveAlternateScreen, cursor::Show);
+}
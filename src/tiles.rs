@@ -0,0 +1,94 @@
+use crate::Dir;
+
+// room art for every door mask, drawn for the configured room size: a
+// square floor in the middle with a corridor running to each open door
+pub struct RoomTiles {
+    pub base: usize,
+    rooms: Vec<String>,
+    unknown: String,
+}
+
+impl RoomTiles {
+    pub fn new(base: usize, door_width: usize) -> RoomTiles {
+        RoomTiles {
+            base,
+            rooms: (0..16).map(|mask| room_gfx(mask, base, door_width)).collect(),
+            unknown: "?".repeat(base * base),
+        }
+    }
+
+    pub fn room(&self, mask: usize) -> &str {
+        &self.rooms[mask]
+    }
+
+    // drawn in place of rooms not visited yet
+    pub fn unknown(&self) -> &str {
+        &self.unknown
+    }
+
+    pub fn tile(&self, mask: usize, x: usize, y: usize) -> char {
+        self.rooms[mask].as_bytes()[y * self.base + x] as char
+    }
+}
+
+// how rooms look on screen; the map itself always uses '#', '.' and '?'
+pub struct Tileset {
+    pub name: &'static str,
+    wall: char,
+    floor: char,
+    unknown: char,
+}
+
+pub static TILESETS: [Tileset; 3] = [
+    Tileset { name: "classic", wall: '#', floor: '.', unknown: '?' },
+    Tileset { name: "open", wall: '#', floor: ' ', unknown: ' ' },
+    Tileset { name: "rubble", wall: '%', floor: ',', unknown: ':' },
+];
+
+impl Tileset {
+    pub fn next(&self) -> &'static Tileset {
+        let idx = TILESETS.iter().position(|t| t.name == self.name).unwrap();
+        &TILESETS[(idx + 1) % TILESETS.len()]
+    }
+
+    pub fn legend(&self) -> [(char, &'static str); 3] {
+        [(self.wall, "wall"), (self.floor, "floor"), (self.unknown, "room not explored yet")]
+    }
+
+    pub fn draw(&self, gfx: &str) -> String {
+        gfx.chars().map(|c| match c {
+            '#' => self.wall,
+            '.' => self.floor,
+            '?' => self.unknown,
+            other => other,
+        }).collect()
+    }
+}
+
+fn room_gfx(mask: usize, base: usize, door_width: usize) -> String {
+    let st = (base - door_width) / 2;
+    let end = st + door_width;
+    let open = |dir: Dir| mask & (1 << dir as usize) != 0;
+    let mut gfx = String::with_capacity(base * base);
+
+    for y in 0..base {
+        for x in 0..base {
+            let mid_x = (st..end).contains(&x);
+            let mid_y = (st..end).contains(&y);
+
+            let floor = match (mid_x, mid_y) {
+                _ if mask == 0 => false,
+                (true, true) => true,
+                (true, false) if y < st => open(Dir::Up),
+                (true, false) => open(Dir::Down),
+                (false, true) if x < st => open(Dir::Left),
+                (false, true) => open(Dir::Right),
+                (false, false) => false,
+            };
+
+            gfx.push(if floor { '.' } else { '#' });
+        }
+    }
+
+    gfx
+}
@@ -0,0 +1,278 @@
+use std::path::Path;
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind};
+use crate::camera::Camera;
+use crate::cli::Options;
+use crate::config;
+use crate::help::KEYS_SECTION;
+use crate::input;
+use crate::palette::{Hue, PALETTES};
+use crate::replay::{self, Replay};
+use crate::score::HighScoresWindowContent;
+use crate::widgets::{Menu, Outcome, TextInput};
+use crate::{Game, GameState, GameVars, Layer, RenderableContent, RunSetup, TerminalImage};
+
+static BANNER: [&str; 5] = [
+    " ____   ____  ___ __  __",
+    "|  _ \\ / ___||_ _|\\ \\/ /",
+    "| | | |\\___ \\ | |  \\  / ",
+    "| |_| | ___) || | /  \\  ",
+    "|____/ |____/|___/_/\\_\\ ",
+];
+
+static MENU: [&str; 6] = ["New game", "Continue", "High scores", "Options", "Help", "Quit"];
+
+// keys picking a menu entry straight away
+static SHORTCUTS: [(char, usize); 5] = [('n', 0), ('c', 1), ('h', 2), ('o', 3), ('q', 5)];
+
+// where the menu and the text next to the skull start, inside the title window
+static TEXT_X: isize = 26;
+
+struct TitleWindowContent {
+    notice: Option<String>,
+}
+
+impl RenderableContent for TitleWindowContent {
+    fn render(&self, _game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let width = cols - TEXT_X as usize;
+        let mut imgs = vec![TerminalImage::new(1, 2, 1)];
+
+        for (y, line) in BANNER.iter().enumerate() {
+            imgs.push(TerminalImage::with_text(line.to_string(), TEXT_X, 1 + y as isize));
+        }
+
+        if let Some(notice) = self.notice.as_ref() {
+            let line: String = notice.chars().take(width).collect();
+            imgs.push(TerminalImage::with_text(line, TEXT_X, rows as isize - 2).colored(Hue::Info));
+        }
+
+        imgs.push(TerminalImage::with_text("arrows: choose  enter: pick".to_string(), TEXT_X, rows as isize - 1));
+        imgs
+    }
+}
+
+impl Game {
+    // title state, left when a game starts or the player quits
+    pub fn title(&mut self, opts: &Options, config_file: Option<&Path>) {
+        // nothing to click or look at in linear mode
+        if self.screen.linear {
+            self.state = GameState::Playing;
+            self.vars.log("D S I X. Arrows move, l looks around, ? helps, q quits.".to_string());
+            return;
+        }
+
+        // the log has nothing to say before the run starts
+        self.screen.set_visible("log", false);
+        self.notice(None);
+
+        let mut selected = 0;
+
+        while self.state == GameState::Title {
+            let mut menu = Menu::new(None, MENU.iter().map(|e| e.to_string()).collect());
+            menu.selected = selected;
+            self.screen.add_window(menu, MENU.len(), 20, 2 + TEXT_X as usize, 13, false, ' ').on(Layer::Modal);
+
+            let picked = self.run_modal(&SHORTCUTS);
+
+            if let Some(Outcome::Picked(i)) = picked {
+                selected = i;
+            }
+
+            match picked {
+                Some(Outcome::Picked(0)) => self.new_game(opts, config_file),
+                Some(Outcome::Picked(1)) => self.continue_run(),
+                Some(Outcome::Picked(2)) => self.page(HighScoresWindowContent::new()),
+                Some(Outcome::Picked(3)) => self.options(),
+                Some(Outcome::Picked(4)) => {
+                    self.open_help(0);
+                    self.run_modal(&[]);
+                },
+                _ => self.state = GameState::Quit,
+            }
+        }
+
+        self.screen.remove_window("title");
+        self.screen.set_visible("log", true);
+    }
+
+    // a line under the menu, telling why a choice did not work out
+    fn notice(&mut self, notice: Option<String>) {
+        self.screen.remove_window("title");
+        self.screen.add_window(TitleWindowContent { notice }, 15, 69, 2, 6, true, ' ').named("title").on(Layer::Overlay);
+    }
+
+    // drives the modal window just added until it answers, then closes it
+    fn run_modal(&mut self, shortcuts: &[(char, usize)]) -> Option<Outcome> {
+        loop {
+            self.render();
+            self.flush_screen();
+
+            let Ok(event) = read() else {
+                self.screen.pop_modal();
+                return None;
+            };
+
+            let outcome = match &event {
+                Event::Key(KeyEvent{code: KeyCode::Char(c), kind: KeyEventKind::Press, ..}) => {
+                    shortcuts.iter().find(|(k, _)| k == c).map(|&(_, i)| Outcome::Picked(i))
+                },
+                _ => None,
+            };
+
+            if let Some(outcome) = outcome.or_else(|| self.screen.dispatch(&event)) {
+                self.screen.pop_modal();
+                return Some(outcome);
+            }
+        }
+    }
+
+    // shows a window over the title until a key is pressed
+    fn page<C: RenderableContent + 'static>(&mut self, content: C) {
+        self.screen.add_window(content, 15, 69, 2, 6, true, ' ').named("page").on(Layer::Overlay);
+        self.render();
+        self.flush_screen();
+        input::wait_press();
+        self.screen.remove_window("page");
+    }
+
+    fn new_game(&mut self, opts: &Options, config_file: Option<&Path>) {
+        let names = match config::preset_names(config_file) {
+            Ok(names) => names,
+            Err(e) => return self.notice(Some(e)),
+        };
+
+        let mut entries = vec!["normal".to_string()];
+        entries.extend(names);
+
+        let mut menu = Menu::new(Some("Difficulty"), entries.clone());
+        menu.selected = opts.difficulty.as_ref().and_then(|d| entries.iter().position(|e| e == d)).unwrap_or(0);
+        self.screen.add_window(menu, (entries.len() + 2).min(8), 24, 32, 12, true, ' ').on(Layer::Modal);
+
+        let difficulty = match self.run_modal(&[]) {
+            Some(Outcome::Picked(0)) => None,
+            Some(Outcome::Picked(i)) => Some(entries[i].as_str()),
+            _ => return,
+        };
+
+        let seed = opts.seed.map(|s| s.to_string()).unwrap_or_default();
+        self.screen.add_window(TextInput::new("Seed, empty for a random one:", &seed, 20, true), 2, 32, 32, 14, true, ' ').on(Layer::Modal);
+
+        let seed = match self.run_modal(&[]) {
+            Some(Outcome::Text(text)) if text.is_empty() => rand::random(),
+            Some(Outcome::Text(text)) => match text.parse::<u64>() {
+                Ok(seed) => seed,
+                Err(_) => return self.notice(Some(format!("Seed must be at most {}.", u64::MAX))),
+            },
+            _ => return,
+        };
+
+        let settings = match config::load(config_file, difficulty) {
+            Ok(settings) => settings,
+            Err(e) => return self.notice(Some(e)),
+        };
+
+        self.reset(&RunSetup::new(seed, opts.start_level.unwrap_or(1), opts.map_size, opts.block_chance, settings));
+        self.state = GameState::Playing;
+    }
+
+    fn continue_run(&mut self) {
+        let Some(path) = replay::latest() else {
+            return self.notice(Some("No run to continue.".to_string()));
+        };
+
+        match Replay::load(&path) {
+            Ok(replay) => {
+                self.reset(&replay.setup);
+
+                if self.resume(replay) {
+                    self.state = GameState::Playing;
+                } else {
+                    self.notice(Some("The last run is over, start a new one.".to_string()));
+                }
+            },
+            Err(e) => self.notice(Some(e)),
+        }
+    }
+
+    // view settings, they apply at once and last until dsix exits
+    fn options(&mut self) {
+        let mut selected = 0;
+
+        loop {
+            let colors = if self.screen.color { self.screen.palette.name } else { "off" };
+            let entries = vec![
+                format!("Colors: {}", colors),
+                format!("Camera: {}", self.vars.camera.mode.name()),
+                format!("Tiles:  {}", self.vars.tileset.name),
+                "Key bindings".to_string(),
+            ];
+
+            let mut menu = Menu::new(Some("Options"), entries);
+            menu.selected = selected;
+            self.screen.add_window(menu, 6, 24, 32, 12, true, ' ').on(Layer::Modal);
+
+            let picked = self.run_modal(&[]);
+
+            if let Some(Outcome::Picked(i)) = picked {
+                selected = i;
+            }
+
+            match picked {
+                Some(Outcome::Picked(0)) => self.next_colors(),
+                Some(Outcome::Picked(1)) => self.vars.camera.cycle(),
+                Some(Outcome::Picked(2)) => self.vars.tileset = self.vars.tileset.next(),
+                Some(Outcome::Picked(_)) => {
+                    self.open_help(KEYS_SECTION);
+                    self.run_modal(&[]);
+                },
+                _ => return,
+            }
+        }
+    }
+
+    // every palette in turn, then no colors at all
+    fn next_colors(&mut self) {
+        let idx = PALETTES.iter().position(|p| p.name == self.screen.palette.name).unwrap_or(0);
+
+        if !self.screen.color {
+            self.screen.color = true;
+            self.screen.palette = &PALETTES[0];
+        } else if idx + 1 == PALETTES.len() {
+            self.screen.color = false;
+        } else {
+            self.screen.palette = &PALETTES[idx + 1];
+        }
+    }
+
+    // fresh run for a setup picked on the title screen; view options stay
+    fn reset(&mut self, setup: &RunSetup) {
+        let mut vars = Game::new(0, 0, setup).vars;
+        vars.camera = Camera::new(self.vars.camera.mode);
+        vars.tileset = self.vars.tileset;
+
+        self.vars = vars;
+        self.setup = *setup;
+        self.resumed.clear();
+    }
+
+    // plays a loaded run up to where it was left, false if it is already over
+    pub fn resume(&mut self, replay: Replay) -> bool {
+        let mut ongoing = true;
+
+        for &(_, action) in &replay.actions {
+            if !self.apply(action) || self.vars.is_dead() {
+                ongoing = false;
+                break;
+            }
+        }
+
+        self.update_shop_window();
+        self.vars.log(format!("Run loaded after {} turns.", self.vars.turn));
+
+        // an ended run is not carried into the next recording
+        if ongoing {
+            self.resumed = replay.actions;
+        }
+
+        ongoing
+    }
+}
@@ -0,0 +1,80 @@
+use rand::Rng;
+use crate::item::ITEMS;
+use crate::GameVars;
+
+// one treasure chest in this many holds a spare torch
+static TORCH_IN_CHEST: usize = 3;
+
+// a torch burns for `settings.torch` turns, 0 turns the whole thing off
+impl GameVars {
+    pub fn torch_on(&self) -> bool {
+        self.settings.torch > 0
+    }
+
+    // rooms away from the hero the torch reaches through open doors: two
+    // while it is fresh, none in its last third, None once it is out
+    pub fn light_radius(&self) -> Option<usize> {
+        if !self.torch_on() {
+            return Some(0);
+        }
+
+        (self.torch > 0).then(|| (self.torch * 3).div_ceil(self.settings.torch) - 1)
+    }
+
+    // once a turn; a spare torch is lit as soon as the old one is out
+    pub fn burn_torch(&mut self) {
+        if !self.torch_on() {
+            return;
+        }
+
+        let before = self.light_radius();
+        self.torch = self.torch.saturating_sub(1);
+
+        match self.inventory.iter().position(|&k| ITEMS[k].torch) {
+            Some(idx) if self.torch == 0 => {
+                self.remove_item(idx);
+                self.torch = self.settings.torch;
+                self.log("You light a fresh torch.".to_string());
+            },
+            _ => {},
+        }
+
+        match self.light_radius() {
+            None if before.is_some() => self.log("Your torch burns out. Darkness closes in!".to_string()),
+            Some(r) if before.is_some_and(|b| b > r) => self.log("Your torch burns lower.".to_string()),
+            _ => {},
+        }
+
+        self.light_rooms();
+    }
+
+    // only while torches are on, the chance roll would change old runs otherwise
+    pub fn chest_torch(&mut self) {
+        if self.torch_on() && self.rng.random_range(0..TORCH_IN_CHEST) == 0 {
+            self.inventory.push(ITEMS.iter().position(|i| i.torch).unwrap());
+            self.log("There is a spare torch in it too.".to_string());
+        }
+    }
+
+    // lit rooms go on the map, in the dark every room but this one fades
+    pub fn light_rooms(&mut self) {
+        let start = self.hero_cell();
+
+        let Some(radius) = self.light_radius() else {
+            for row in 0..self.map.matrix.rows {
+                for col in 0..self.map.matrix.cols {
+                    if (row, col) != start {
+                        self.visit_map.forget(row, col);
+                    }
+                }
+            }
+            return;
+        };
+
+        for ((row, col), dist) in self.rooms_by_distance() {
+            if dist <= radius {
+                self.visit_map.see(row, col);
+            }
+        }
+    }
+}
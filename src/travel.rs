@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use crate::ai::SIGHT_RANGE;
+use crate::{Dir, GameVars};
+
+// safety net against walking in circles forever
+static MAX_TRAVEL_STEPS: usize = 1000;
+
+enum Goal {
+    Explore,
+    Cell(usize, usize),
+    Tile(usize, usize),
+}
+
+impl GameVars {
+    pub fn start_travel_select(&mut self) {
+        self.travel_cursor = Some(self.hero_cell());
+        self.log("Pick a visited room to travel to (enter: go, esc: cancel).".to_string());
+    }
+
+    pub fn move_travel_cursor(&mut self, dir: Dir) {
+        let Some((row, col)) = self.travel_cursor else { return };
+
+        if let Some(cell) = self.map.get_neighbor(row, col, dir) {
+            self.travel_cursor = Some(cell);
+        }
+    }
+
+    pub fn confirm_travel(&mut self) {
+        let Some((row, col)) = self.travel_cursor.take() else { return };
+
+        if !self.visit_map.known(row, col) {
+            self.log("You don't know the way to that room.".to_string());
+            return;
+        }
+
+        self.travel(Goal::Cell(row, col));
+    }
+
+    pub fn travel_to(&mut self, x: usize, y: usize) {
+        let inside = x < self.map.matrix.cols * self.base && y < self.map.matrix.rows * self.base;
+
+        if !inside || !self.visit_map.known(y / self.base, x / self.base) || !self.is_walkable(x, y) {
+            self.log("You don't know the way there.".to_string());
+            return;
+        }
+
+        self.travel(Goal::Tile(x, y));
+    }
+
+    pub fn auto_explore(&mut self) {
+        self.travel(Goal::Explore);
+    }
+
+    // every room reachable from the hero through doors they know of, nearest
+    // first, with its distance in rooms
+    pub fn rooms_by_distance(&self) -> Vec<((usize, usize), usize)> {
+        let start = self.hero_cell();
+        let mut seen = vec![start];
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut order = Vec::new();
+
+        while let Some(((row, col), dist)) = queue.pop_front() {
+            order.push(((row, col), dist));
+
+            for dir in [Dir::Up, Dir::Right, Dir::Down, Dir::Left] {
+                if self.visible_doors(row, col) & (1 << dir as usize) == 0 {
+                    continue;
+                }
+
+                match self.map.get_neighbor(row, col, dir) {
+                    Some(next) if !seen.contains(&next) => {
+                        seen.push(next);
+                        queue.push_back((next, dist + 1));
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        order
+    }
+
+    // closest room not entered yet, reachable through known doors; a room
+    // only lit by the torch still counts as unexplored
+    fn nearest_unvisited(&self) -> Option<(usize, usize)> {
+        self.rooms_by_distance().into_iter()
+            .map(|(cell, _)| cell)
+            .find(|&(row, col)| !self.visit_map.entered(row, col))
+    }
+
+    fn monster_in_sight(&self) -> bool {
+        self.monsters.iter().any(|m| {
+            let dist = m.pos_x.abs_diff(self.hero_pos_x) + m.pos_y.abs_diff(self.hero_pos_y);
+            dist <= SIGHT_RANGE && self.visit_map.known(m.pos_y / self.base, m.pos_x / self.base)
+        })
+    }
+
+    // walks step by step, stopping as soon as anything happens around the hero
+    fn travel(&mut self, goal: Goal) {
+        if self.monster_in_sight() {
+            self.log("Not with monsters around!".to_string());
+            return;
+        }
+
+        for _ in 0..MAX_TRAVEL_STEPS {
+            let target = match goal {
+                Goal::Cell(row, col) => (col * self.base + self.base / 2, row * self.base + self.base / 2),
+                Goal::Tile(x, y) => (x, y),
+                Goal::Explore => match self.nearest_unvisited() {
+                    Some((row, col)) => (col * self.base + self.base / 2, row * self.base + self.base / 2),
+                    None => {
+                        self.log("There is nothing left to explore.".to_string());
+                        return;
+                    },
+                },
+            };
+
+            if target == (self.hero_pos_x, self.hero_pos_y) {
+                return;
+            }
+
+            let Some((x, y)) = self.path_step((self.hero_pos_x, self.hero_pos_y), target) else {
+                self.log("You can't find a way there.".to_string());
+                return;
+            };
+
+            let dir = if x > self.hero_pos_x {
+                Dir::Right
+            } else if x < self.hero_pos_x {
+                Dir::Left
+            } else if y > self.hero_pos_y {
+                Dir::Down
+            } else {
+                Dir::Up
+            };
+
+            let (log_len, hp) = (self.log.len(), self.hp);
+            self.move_hero(dir);
+
+            if self.log.len() != log_len || self.hp < hp || self.shop.is_some() || self.monster_in_sight() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::sync::LazyLock;
+use rand::Rng;
+use serde::Deserialize;
+use crate::monster::{Behavior, Monster, MONSTERS};
+use crate::room::RoomKind;
+use crate::{Dir, GameVars};
+
+static VAULT_FILE: &str = include_str!("../data/vaults.toml");
+
+// chance (out of 100) of an ordinary floor getting a vault
+static VAULT_CHANCE: usize = 50;
+static PLACE_TRIES: usize = 20;
+
+static DIRS: [(char, Dir); 4] = [('U', Dir::Up), ('R', Dir::Right), ('D', Dir::Down), ('L', Dir::Left)];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VaultDef {
+    name: String,
+    #[serde(default = "first_level")]
+    min_level: usize,
+    rooms: Vec<String>,
+}
+
+fn first_level() -> usize {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VaultFile {
+    vault: Vec<VaultDef>,
+    arena: Vec<VaultDef>,
+}
+
+// door mask and kind of every room, row by row
+pub struct Vault {
+    pub name: String,
+    min_level: usize,
+    rooms: Vec<Vec<(usize, RoomKind)>>,
+}
+
+pub struct Vaults {
+    vaults: Vec<Vault>,
+    arenas: Vec<Vault>,
+}
+
+// the file is built in, a mistake in it is caught the first time a floor is made
+pub static VAULTS: LazyLock<Vaults> = LazyLock::new(|| {
+    parse(VAULT_FILE).unwrap_or_else(|e| panic!("data/vaults.toml: {}", e))
+});
+
+// "LRD$": doors first, what the room holds last
+fn parse_room(token: &str) -> Result<(usize, RoomKind), String> {
+    let mut chars: Vec<char> = token.chars().collect();
+
+    let kind = match chars.pop() {
+        Some('.') => RoomKind::Empty,
+        Some('$') => RoomKind::Treasure,
+        Some('+') => RoomKind::Shrine,
+        Some('M') => RoomKind::Merchant,
+        Some('^') => RoomKind::Trap,
+        Some('&') => RoomKind::Lair,
+        _ => return Err(format!("room '{}' must end with one of . $ + M ^ &", token)),
+    };
+
+    let mut mask = 0;
+
+    for c in chars {
+        let (_, dir) = DIRS.iter().find(|(d, _)| *d == c).ok_or(format!("room '{}' has an unknown door '{}'", token, c))?;
+        mask |= 1 << *dir as usize;
+    }
+
+    Ok((mask, kind))
+}
+
+fn parse_vault(def: VaultDef) -> Result<Vault, String> {
+    let rooms = def.rooms.iter()
+        .map(|line| line.split_whitespace().map(parse_room).collect::<Result<Vec<_>, String>>())
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| format!("{}: {}", def.name, e))?;
+
+    let width = rooms.first().map(|r| r.len()).unwrap_or(0);
+
+    if width == 0 || rooms.iter().any(|r| r.len() != width) {
+        return Err(format!("{}: every row needs the same number of rooms", def.name));
+    }
+
+    // inner doors come in pairs, one on each side
+    for (row, line) in rooms.iter().enumerate() {
+        for (col, &(mask, _)) in line.iter().enumerate() {
+            for (name, dir) in DIRS {
+                let next = match dir {
+                    Dir::Up => row.checked_sub(1).map(|r| (r, col)),
+                    Dir::Down => Some((row + 1, col)).filter(|&(r, _)| r < rooms.len()),
+                    Dir::Left => col.checked_sub(1).map(|c| (row, c)),
+                    Dir::Right => Some((row, col + 1)).filter(|&(_, c)| c < width),
+                };
+
+                let Some((r, c)) = next else { continue };
+                let here = mask & (1 << dir as usize) != 0;
+                let there = rooms[r][c].0 & (1 << dir.opposite() as usize) != 0;
+
+                if here != there {
+                    return Err(format!("{}: door {} of room {},{} has no match", def.name, name, row + 1, col + 1));
+                }
+            }
+        }
+    }
+
+    Ok(Vault { name: def.name, min_level: def.min_level, rooms })
+}
+
+fn parse(text: &str) -> Result<Vaults, String> {
+    let file: VaultFile = toml::from_str(text).map_err(|e| e.message().to_string())?;
+    let vaults = file.vault.into_iter().map(parse_vault).collect::<Result<Vec<_>, String>>()?;
+    let arenas = file.arena.into_iter().map(parse_vault).collect::<Result<Vec<_>, String>>()?;
+
+    for arena in &arenas {
+        let w = arena.width();
+        let entrance = arena.rooms[arena.height() - 1][w / 2].0;
+
+        if w < 3 || w.is_multiple_of(2) || entrance & (1 << Dir::Down as usize) == 0 {
+            return Err(format!("{}: an arena needs an odd width of 3 or more and a door down in the bottom middle", arena.name));
+        }
+    }
+
+    Ok(Vaults { vaults, arenas })
+}
+
+impl Vault {
+    fn width(&self) -> usize {
+        self.rooms[0].len()
+    }
+
+    fn height(&self) -> usize {
+        self.rooms.len()
+    }
+}
+
+impl GameVars {
+    pub fn is_boss_floor(&self) -> bool {
+        self.settings.boss_every > 0 && self.level.is_multiple_of(self.settings.boss_every)
+    }
+
+    // nothing to search for between the two rooms, door or not
+    fn clear_secret(&mut self, row: usize, col: usize, dir: Dir) {
+        self.secrets.reveal(row, col, dir);
+
+        if let Some((r, c)) = self.map.get_neighbor(row, col, dir) {
+            self.secrets.reveal(r, c, dir.opposite());
+        }
+    }
+
+    // the vault's top-left room goes to (row, col), doors and kinds replace
+    // whatever was generated there, secret doors included
+    fn stamp(&mut self, vault: &Vault, row: usize, col: usize) {
+        for (dy, line) in vault.rooms.iter().enumerate() {
+            for (dx, &(mask, kind)) in line.iter().enumerate() {
+                for (_, dir) in DIRS {
+                    if mask & (1 << dir as usize) != 0 {
+                        self.map.unblock(row + dy, col + dx, dir);
+                    } else {
+                        self.map.block(row + dy, col + dx, dir);
+                    }
+
+                    self.clear_secret(row + dy, col + dx, dir);
+                }
+
+                self.rooms.set(row + dy, col + dx, kind);
+            }
+        }
+    }
+
+    // sometimes a vault on an ordinary floor, away from where the hero stands
+    pub fn add_vault(&mut self) {
+        let (rows, cols) = (self.map.matrix.rows, self.map.matrix.cols);
+        let (hero_row, hero_col) = self.hero_cell();
+
+        if self.rng.random_range(0..100) >= VAULT_CHANCE {
+            return;
+        }
+
+        // the outer ring of rooms is sealed, a vault keeps a room away from it
+        // so its edge doors never open into the ring
+        let fitting: Vec<&Vault> = VAULTS.vaults.iter()
+            .filter(|v| v.min_level <= self.level && v.width() + 4 <= cols && v.height() + 4 <= rows)
+            .collect();
+
+        if fitting.is_empty() {
+            return;
+        }
+
+        let vault = fitting[self.rng.random_range(0..fitting.len())];
+
+        for _ in 0..PLACE_TRIES {
+            let row = self.rng.random_range(2..=rows - 2 - vault.height());
+            let col = self.rng.random_range(2..=cols - 2 - vault.width());
+
+            // not even next to the start room, which gets all its doors opened
+            let near_rows = row - 1..=row + vault.height();
+            let near_cols = col - 1..=col + vault.width();
+
+            if !(near_rows.contains(&hero_row) && near_cols.contains(&hero_col)) {
+                self.stamp(vault, row, col);
+                return;
+            }
+        }
+    }
+
+    // boss floors: an arena at the top middle and a corridor from the start
+    // room to its entrance; returns the middle room, or None if it won't fit.
+    // it keeps off the top edge unless the floor is too short for that
+    pub fn add_arena(&mut self) -> Option<(usize, usize)> {
+        let cols = self.map.matrix.cols;
+        let (hero_row, hero_col) = self.hero_cell();
+
+        let fitting: Vec<&Vault> = VAULTS.arenas.iter()
+            .filter(|a| hero_row >= a.height() && a.width() / 2 <= hero_col && hero_col + a.width() / 2 < cols)
+            .collect();
+
+        if fitting.is_empty() {
+            return None;
+        }
+
+        let arena = fitting[self.rng.random_range(0..fitting.len())];
+        let top = (hero_row - arena.height()).min(1);
+        self.stamp(arena, top, hero_col - arena.width() / 2);
+
+        for row in top + arena.height() + 1..=hero_row {
+            self.map.unblock(row, hero_col, Dir::Up);
+            self.clear_secret(row, hero_col, Dir::Up);
+        }
+
+        Some((top + arena.height() / 2, hero_col))
+    }
+
+    // a boss of its own for every boss floor, guarding the way down
+    pub fn spawn_boss(&mut self, (row, col): (usize, usize)) {
+        let bosses: Vec<usize> = (0..MONSTERS.len()).filter(|&k| MONSTERS[k].boss).collect();
+        let kind = bosses[(self.level / self.settings.boss_every - 1) % bosses.len()];
+        let hp = MONSTERS[kind].hp_dice().roll(&mut self.rng).total.max(1);
+        let (x, y) = (col * self.base + self.base / 2, row * self.base + self.base / 2);
+
+        let mut boss = Monster::new(kind, x, y, hp);
+        boss.behavior = Behavior::Guard;
+        boss.home = Some((x, y));
+
+        self.monsters.retain(|m| (m.pos_x, m.pos_y) != (x, y));
+        self.monsters.push(boss);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one vault and one arena, each given as its rows of rooms
+    fn file(vault: &str, arena: &str) -> String {
+        format!("[[vault]]\nname = \"v\"\nrooms = [{}]\n\n[[arena]]\nname = \"a\"\nrooms = [{}]\n", vault, arena)
+    }
+
+    #[test]
+    fn built_in_file_parses() {
+        let vaults = parse(VAULT_FILE).unwrap_or_else(|e| panic!("{}", e));
+        assert!(!vaults.vaults.is_empty());
+        assert!(!vaults.arenas.is_empty());
+    }
+
+    #[test]
+    fn accepts_a_small_file() {
+        assert!(parse(&file(r#""R$ L.""#, r#""R. LRD. L.""#)).is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_vaults() {
+        let arena = r#""R. LRD. L.""#;
+
+        assert_eq!(parse(&file(r#""R$ .""#, arena)).err(), Some("v: door R of room 1,1 has no match".to_string()));
+        assert_eq!(parse(&file(r#""RD$ LD.", "U.""#, arena)).err(), Some("v: every row needs the same number of rooms".to_string()));
+        assert_eq!(parse(&file(r#""X.""#, arena)).err(), Some("v: room 'X.' has an unknown door 'X'".to_string()));
+        assert_eq!(parse(&file(r#""R""#, arena)).err(), Some("v: room 'R' must end with one of . $ + M ^ &".to_string()));
+    }
+
+    #[test]
+    fn rejects_malformed_arenas() {
+        let vault = r#""R. L.""#;
+
+        assert_eq!(
+            parse(&file(vault, r#""R. LR. LRD. L.""#)).err(),
+            Some("a: an arena needs an odd width of 3 or more and a door down in the bottom middle".to_string()),
+        );
+        assert_eq!(
+            parse(&file(vault, r#""R. LR. L.""#)).err(),
+            Some("a: an arena needs an odd width of 3 or more and a door down in the bottom middle".to_string()),
+        );
+    }
+}
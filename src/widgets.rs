@@ -0,0 +1,230 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crate::palette::Hue;
+use crate::{GameVars, RenderableContent, TerminalImage};
+
+// what a modal widget hands back once the player is done with it
+#[derive(Clone, PartialEq)]
+pub enum Outcome {
+    Picked(usize),
+    Yes,
+    No,
+    Text(String),
+    Closed,
+}
+
+pub fn pressed(event: &Event) -> Option<KeyCode> {
+    match event {
+        Event::Key(KeyEvent{code, kind: KeyEventKind::Press, ..}) => Some(*code),
+        _ => None,
+    }
+}
+
+fn clip(text: &str, cols: usize) -> String {
+    text.chars().take(cols).collect()
+}
+
+// moves images rendered by a widget to a spot inside the parent content
+pub fn place(imgs: Vec<TerminalImage>, x: isize, y: isize) -> Vec<TerminalImage> {
+    imgs.into_iter().map(|mut img| {
+        img.pos_x += x;
+        img.pos_y += y;
+        img.end_x += x;
+        img.end_y += y;
+        img
+    }).collect()
+}
+
+// selectable list, scrolls to keep the selected entry in view
+pub struct Menu {
+    title: Option<String>,
+    entries: Vec<String>,
+    pub selected: usize,
+}
+
+impl Menu {
+    pub fn new(title: Option<&str>, entries: Vec<String>) -> Menu {
+        Menu { title: title.map(str::to_string), entries, selected: 0 }
+    }
+
+    pub fn select(&mut self, delta: isize) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected as isize + delta).rem_euclid(self.entries.len() as isize) as usize;
+        }
+    }
+
+    fn top(&self) -> usize {
+        if self.title.is_some() { 2 } else { 0 }
+    }
+
+    // first entry shown and how many fit below the title
+    fn scroll(&self, rows: usize) -> (usize, usize) {
+        let visible = rows.saturating_sub(self.top()).max(1);
+        (self.selected.saturating_sub(visible - 1), visible)
+    }
+}
+
+impl RenderableContent for Menu {
+    fn render(&self, _game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let mut imgs = Vec::new();
+        let top = self.top();
+
+        if let Some(title) = self.title.as_ref() {
+            imgs.push(TerminalImage::with_text(clip(title, cols - 1), 1, 0));
+        }
+
+        let (st, visible) = self.scroll(rows);
+
+        for (i, entry) in self.entries.iter().enumerate().skip(st).take(visible) {
+            let y = (top + i - st) as isize;
+
+            if i == self.selected {
+                imgs.push(TerminalImage::with_text(clip(&format!(">{}", entry), cols - 1), 1, y).colored(Hue::Cursor));
+            } else {
+                imgs.push(TerminalImage::with_text(clip(&format!(" {}", entry), cols - 1), 1, y));
+            }
+        }
+
+        imgs
+    }
+
+    fn handle(&mut self, event: &Event) -> Option<Outcome> {
+        match pressed(event)? {
+            KeyCode::Up => self.select(-1),
+            KeyCode::Down => self.select(1),
+            KeyCode::Enter if !self.entries.is_empty() => return Some(Outcome::Picked(self.selected)),
+            KeyCode::Esc => return Some(Outcome::Closed),
+            _ => {},
+        }
+        None
+    }
+
+    fn click(&mut self, _x: usize, y: usize, rows: usize) -> Option<Outcome> {
+        let idx = self.scroll(rows).0 + y.checked_sub(self.top())?;
+        (idx < self.entries.len()).then_some(Outcome::Picked(idx))
+    }
+}
+
+// yes/no question, "no" is picked until the player says otherwise
+pub struct Dialog {
+    question: String,
+    yes: bool,
+}
+
+impl Dialog {
+    pub fn new(question: &str) -> Dialog {
+        Dialog { question: question.to_string(), yes: false }
+    }
+}
+
+impl RenderableContent for Dialog {
+    fn render(&self, _game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let yes = TerminalImage::with_text("[ yes ]".to_string(), 2, rows as isize - 1);
+        let no = TerminalImage::with_text("[ no ]".to_string(), 11, rows as isize - 1);
+
+        vec![
+            TerminalImage::with_text(clip(&self.question, cols - 2), 1, 0),
+            if self.yes { yes.colored(Hue::Cursor) } else { yes },
+            if self.yes { no } else { no.colored(Hue::Cursor) },
+        ]
+    }
+
+    fn handle(&mut self, event: &Event) -> Option<Outcome> {
+        match pressed(event)? {
+            KeyCode::Left | KeyCode::Right | KeyCode::Tab => self.yes = !self.yes,
+            KeyCode::Char('y') => return Some(Outcome::Yes),
+            KeyCode::Char('n') | KeyCode::Esc => return Some(Outcome::No),
+            KeyCode::Enter => return Some(if self.yes { Outcome::Yes } else { Outcome::No }),
+            _ => {},
+        }
+        None
+    }
+
+    fn click(&mut self, x: usize, y: usize, rows: usize) -> Option<Outcome> {
+        match x {
+            _ if y + 1 != rows => None,
+            2..9 => Some(Outcome::Yes),
+            11..17 => Some(Outcome::No),
+            _ => None,
+        }
+    }
+}
+
+// single line of text, optionally digits only
+pub struct TextInput {
+    label: String,
+    pub text: String,
+    max_len: usize,
+    digits: bool,
+}
+
+impl TextInput {
+    pub fn new(label: &str, text: &str, max_len: usize, digits: bool) -> TextInput {
+        TextInput { label: label.to_string(), text: text.to_string(), max_len, digits }
+    }
+}
+
+impl RenderableContent for TextInput {
+    fn render(&self, _game: &GameVars, _rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let field = format!("{}_", self.text);
+
+        vec![
+            TerminalImage::with_text(clip(&self.label, cols - 2), 1, 0),
+            TerminalImage::with_text(clip(&field, cols - 2), 1, 1).colored(Hue::Cursor),
+        ]
+    }
+
+    fn handle(&mut self, event: &Event) -> Option<Outcome> {
+        match pressed(event)? {
+            KeyCode::Char(c) if self.text.len() < self.max_len && (!self.digits || c.is_ascii_digit()) => self.text.push(c),
+            KeyCode::Backspace => { self.text.pop(); },
+            KeyCode::Enter => return Some(Outcome::Text(self.text.clone())),
+            KeyCode::Esc => return Some(Outcome::Closed),
+            _ => {},
+        }
+        None
+    }
+}
+
+// "HP [#####.....]", built fresh for every frame
+pub struct ProgressBar {
+    label: String,
+    value: usize,
+    max: usize,
+}
+
+impl ProgressBar {
+    pub fn new(label: &str, value: usize, max: usize) -> ProgressBar {
+        ProgressBar { label: label.to_string(), value, max }
+    }
+}
+
+impl RenderableContent for ProgressBar {
+    fn render(&self, _game: &GameVars, _rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let width = cols.saturating_sub(self.label.len() + 3).max(1);
+        let filled = (self.value.min(self.max) * width).checked_div(self.max).unwrap_or(0);
+        let bar = format!("{} [{}{}]", self.label, "#".repeat(filled), ".".repeat(width - filled));
+
+        vec![TerminalImage::with_text(clip(&bar, cols), 0, 0)]
+    }
+}
+
+// labels in one column, values lined up in the next
+pub struct Table {
+    rows: Vec<(String, String)>,
+}
+
+impl Table {
+    pub fn new(rows: Vec<(&str, String)>) -> Table {
+        Table { rows: rows.into_iter().map(|(k, v)| (k.to_string(), v)).collect() }
+    }
+}
+
+impl RenderableContent for Table {
+    fn render(&self, _game: &GameVars, rows: usize, cols: usize) -> Vec<TerminalImage> {
+        let key_w = self.rows.iter().map(|(k, _)| k.len()).max().unwrap_or(0) + 1;
+
+        self.rows.iter().take(rows).enumerate().map(|(y, (k, v))| {
+            TerminalImage::with_text(clip(&format!("{:<w$}{}", k, v, w = key_w), cols), 0, y as isize)
+        }).collect()
+    }
+}